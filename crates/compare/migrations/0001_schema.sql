@@ -1,8 +1,9 @@
+-- Columns follow the data files, timestamps are kept as seconds since the epoch
 create table if not exists user_transactions (
-    trans_time timestamp not null,
-    user_addr character[16] not null,
-    trans_hash character[16] not null
+    user_addr text not null,
+    trans_time bigint not null,
+    trans_hash text not null
 );
 
-create index transactions_index on user_transactions
+create index if not exists transactions_index on user_transactions
 (user_addr, trans_time) include (trans_hash);
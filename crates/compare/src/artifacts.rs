@@ -0,0 +1,125 @@
+use crate::runtime::Runtime;
+use crate::timeout::{Phase, Timeouts};
+
+/// Directory the per-run artifacts directories are created in.
+pub const ARTIFACTS_DIR_ENV: &str = "DB_TEST_ARTIFACTS_DIR";
+
+/// Something worth keeping from a container once the run is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// Server output, saved as `server.log`
+    Logs,
+    /// File or directory copied out of the container
+    Path(&'static str),
+    /// Output of a `bash -c` script run inside the container, saved as `{name}.txt`
+    Command {
+        name: &'static str,
+        script: &'static str,
+    },
+}
+
+impl Artifact {
+    const LOGS_FILE: &str = "server.log";
+
+    async fn collect(
+        &self,
+        runtime: &Runtime,
+        timeouts: &Timeouts,
+        container_name: &str,
+        dest_dir: &std::path::Path,
+    ) -> anyhow::Result<()> {
+        match self {
+            Artifact::Logs => {
+                let logs = runtime.logs(container_name).await?;
+                tokio::fs::write(dest_dir.join(Self::LOGS_FILE), logs).await?;
+            }
+            Artifact::Path(path) => {
+                let path = std::path::Path::new(path);
+                let dest_dir = dest_dir.to_path_buf();
+                runtime
+                    .download_archive(container_name, path, dest_dir)
+                    .await?;
+            }
+            Artifact::Command { name, script } => {
+                let cmd = vec!["bash", "-c", script];
+                let exec_id = runtime.create_exec(container_name, cmd).await?;
+                let output = timeouts.exec(runtime, &exec_id, Phase::Teardown).await?;
+                let dest_file = dest_dir.join(format!("{name}.txt"));
+                tokio::fs::write(dest_file, output.stdout + &output.stderr).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fresh directory for this run under `base_dir`, named after the start time.
+pub fn run_dir(base_dir: &std::path::Path) -> std::path::PathBuf {
+    let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    base_dir.join(format!("run-{}", started.as_secs()))
+}
+
+/// Stores `artifacts` of the container in `dest_dir`.
+///
+/// Every artifact is attempted even if some of them fail, as they are only
+/// used for debugging. Commands are killed once they exceed the exec limit
+/// of `timeouts`.
+pub async fn collect(
+    runtime: &Runtime,
+    timeouts: &Timeouts,
+    container_name: &str,
+    artifacts: &[Artifact],
+    dest_dir: &std::path::Path,
+) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dest_dir).await?;
+    let mut errors = vec![];
+    for artifact in artifacts {
+        let collect = artifact.collect(runtime, timeouts, container_name, dest_dir);
+        if let Err(err) = collect.await {
+            errors.push(format!("{artifact:?}: {err:#}"));
+        }
+    }
+    anyhow::ensure!(errors.is_empty(), "{}", errors.join("; "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ContainerSpec, ExecOutput, FakeRuntime};
+
+    #[tokio::test]
+    async fn collect_stores_every_artifact() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        fake.script_exec(
+            "du",
+            ExecOutput {
+                stdout: "42\t.\n".into(),
+                ..Default::default()
+            },
+        );
+        let runtime = Runtime::new(fake.clone());
+        runtime
+            .create_container("artifacts", &ContainerSpec::new("image"))
+            .await?;
+        runtime.start_container("artifacts").await?;
+        let dest_dir = std::env::temp_dir().join("db-test-compare/collect_stores_every_artifact");
+        let _ = std::fs::remove_dir_all(&dest_dir);
+        let artifacts = [
+            Artifact::Logs,
+            Artifact::Path("/etc/app.conf"),
+            Artifact::Command {
+                name: "data-dir",
+                script: "du -sb .",
+            },
+        ];
+        let timeouts = Timeouts::default();
+        collect(&runtime, &timeouts, "artifacts", &artifacts, &dest_dir).await?;
+        assert!(dest_dir.join("server.log").exists());
+        assert!(dest_dir.join("app.conf").exists());
+        let data_dir = std::fs::read_to_string(dest_dir.join("data-dir.txt"))?;
+        assert_eq!(data_dir, "42\t.\n");
+        Ok(())
+    }
+}
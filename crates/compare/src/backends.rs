@@ -1,3 +1,7 @@
+pub mod clickhouse;
+pub mod mongo;
+pub mod mysql;
+pub mod postgres;
 pub mod redis;
-// pub mod postgres;
-// pub mod sqlite;
+pub mod scylla;
+pub mod sqlite;
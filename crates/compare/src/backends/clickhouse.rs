@@ -0,0 +1,172 @@
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod client;
+pub mod insert_bulk;
+
+/// ClickHouse keeping the transactions in a MergeTree table sorted by user
+/// and timestamp.
+///
+/// Only the exec load mode is supported, the load runs through the client
+/// shipped in the image.
+pub struct ClickHouse {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use ClickHouse as Backend;
+
+impl crate::docker::Docker for Backend {
+    const IMAGE_NAME: &'static str = "clickhouse/clickhouse-server";
+    const CONTAINER_NAME_PREFIX: &'static str = "bench-clickhouse";
+    const DATA_DIR: Option<&'static str> = Some("/var/lib/clickhouse");
+    const ARTIFACTS: &'static [Artifact] = &[
+        Artifact::Logs,
+        Artifact::Command {
+            name: "settings",
+            script: "clickhouse-client --query 'select * from system.settings where changed'",
+        },
+        Artifact::Command {
+            name: "parts",
+            script: "clickhouse-client --query \
+                     \"select * from system.parts where table = 'user_transactions'\"",
+        },
+        Artifact::Command {
+            name: "data-dir",
+            script: "du -ab /var/lib/clickhouse",
+        },
+    ];
+
+    /// Keeps the default user reachable without a password.
+    fn container_spec() -> ContainerSpec {
+        ContainerSpec::new(Self::IMAGE_NAME).env("CLICKHOUSE_SKIP_USER_SETUP=1")
+    }
+
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        client::Client::parse_load_output(output)
+    }
+
+    /// Compressed size of the table once its parts are merged.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let client = client::Client::new(config, container_name);
+        let sql = "optimize table user_transactions final; \
+                   select sum(bytes_on_disk) from system.parts where active \
+                   and database = currentDatabase() and table = 'user_transactions'";
+        let output = client.query(sql, Phase::Footprint).await?;
+        let disk_bytes = output
+            .lines()
+            .next_back()
+            .ok_or(anyhow::anyhow!("no table size in: {output:?}"))?;
+        Ok(Footprint {
+            memory_bytes: None,
+            disk_bytes: Some(disk_bytes.trim().parse()?),
+        })
+    }
+}
+
+impl crate::Backend for Backend {
+    const NAME: &'static str = "clickhouse";
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+    const LOAD_MODES: &'static [crate::LoadMode] = &[crate::LoadMode::Exec];
+
+    type Instance = crate::docker::Instance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<crate::docker::Instance> {
+        crate::docker::ensure_exec_load(Self::NAME, &self.config, input)?;
+        self.containers_pool.provision().await
+    }
+
+    async fn ready(&self, instance: &mut crate::docker::Instance) -> anyhow::Result<()> {
+        let client = client::Client::new(&self.config, &instance.container_name);
+        client.wait_ready().await
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut crate::docker::Instance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let client = client::Client::new(&self.config, &instance.container_name);
+        client.migrate().await?;
+        instance.load = Some(insert_bulk::prepare(&client, input).await?);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: crate::docker::Instance) -> anyhow::Result<Self::Bencher> {
+        crate::docker::Bench::from_instance(self.config.clone(), instance)
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is stored once with its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let client = &client::Client::new(&self.config, container_name);
+        let query = |sql: String| async move { client.query(&sql, Phase::Verify).await };
+        verify::sql_rows(input, &outcome.loader, '\t', query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::client::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::Runtime;
+    use crate::testing::stdout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("clickhouse_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = crate::testing::script_sql_rows(&fake, &bench_input, '\t')?;
+        fake.script_exec("optimize table", stdout("4096\n"));
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend = ClickHouse::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(4096));
+
+        // A missing row fails the verification
+        fake.script_exec("count(*)", stdout(format!("9\t{}\n", expected.users)));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+}
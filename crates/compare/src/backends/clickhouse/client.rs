@@ -0,0 +1,126 @@
+use super::ClickHouse;
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// Columns of the Postgres migrations, the sorting key replaces the index.
+const SCHEMA: &str = "\
+create table if not exists user_transactions (
+    user_addr String,
+    trans_time UInt64,
+    trans_hash String
+) engine = MergeTree
+order by (user_addr, trans_time)";
+
+/// Runs SQL through `clickhouse-client` inside a started container.
+pub(super) struct Client<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+}
+
+impl<'a> Client<'a> {
+    const READY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    pub(super) fn new(config: &'a crate::BackendConfig, container_name: &'a str) -> Self {
+        Client {
+            config,
+            container_name,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// `clickhouse-client` running the `;` separated `sql`, printing tab
+    /// separated rows.
+    pub(super) fn command(sql: &str) -> Vec<&str> {
+        vec!["clickhouse-client", "--multiquery", "--query", sql]
+    }
+
+    /// Runs `sql` as part of `phase`, returning its rows.
+    pub(super) async fn query(&self, sql: &str, phase: Phase) -> anyhow::Result<String> {
+        let cmd = Self::command(sql);
+        let output = ClickHouse::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// The server reports errors as `Code: N. DB::Exception: …` on stderr.
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !output.stderr.contains("Exception"),
+            "clickhouse-client failed: {}",
+            output.stderr.trim()
+        );
+        Ok(())
+    }
+
+    /// Waits until the server answers queries.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let cmd = Self::command("select 1");
+            let output = ClickHouse::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.trim() == "1" {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Creates the table.
+    pub(super) async fn migrate(&self) -> anyhow::Result<()> {
+        self.query(SCHEMA, Phase::LoadFixture)
+            .await
+            .map_err(|err| anyhow::anyhow!("schema creation failed: {err}"))?;
+        Ok(())
+    }
+
+    /// Fails on the errors of the insert, which prints neither a row count
+    /// nor a server time.
+    ///
+    /// The stored rows are counted by [`Backend::verify`](crate::Backend::verify)
+    /// outside of the measurement.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        Self::ensure_no_error(output)?;
+        Ok(LoaderReport::default())
+    }
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use crate::runtime::FakeRuntime;
+
+    /// Fake runtime whose servers are ready right away.
+    pub(in crate::backends::clickhouse) fn ready_fake() -> FakeRuntime {
+        crate::testing::ready_fake("select 1", "1\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let loader = Client::parse_load_output(&ExecOutput::default())?;
+        assert_eq!(loader, LoaderReport::default());
+
+        let output = ExecOutput {
+            stdout: String::new(),
+            stderr: "Code: 27. DB::Exception: Cannot parse input: expected ',' before: \
+                     '\\n': (at row 3)\n"
+                .into(),
+            ..Default::default()
+        };
+        assert!(Client::parse_load_output(&output).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use db_test_model::temp::CsvFilesManager;
+
+use super::ClickHouse;
+use super::client::Client;
+use crate::docker::{Docker, Load};
+use crate::workload::WorkloadInput;
+
+/// Measured load piping the uploaded data file into a single
+/// `INSERT … FORMAT CSV`.
+pub(super) async fn prepare(client: &Client<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    // Upload data file
+    let config = client.config();
+    let container_name = client.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = CsvFilesManager::tar_data_file(&input.file_path, dst_file)?;
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
+
+    // Prepare load exec
+    let command = format!(
+        "clickhouse-client --query 'INSERT INTO user_transactions \
+         (user_addr, trans_time, trans_hash) FORMAT CSV' < {data_file}"
+    );
+    let command = vec!["bash", "-c", command.as_str()];
+    let exec_id = ClickHouse::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::client::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_creates_table_and_uploads_dataset() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let backend = ClickHouse::setup(BackendConfig::new(Runtime::new(fake.clone()))).await;
+        let file_path = crate::testing::gen_test_csv("clickhouse_prepare_uploads_dataset", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-clickhouse-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert!(execs[1].starts_with("clickhouse-client --multiquery --query create table"));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "bash -c clickhouse-client --query 'INSERT INTO user_transactions \
+                 (user_addr, trans_time, trans_hash) FORMAT CSV' < /tmp/items"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
+
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader, Default::default());
+        Ok(())
+    }
+}
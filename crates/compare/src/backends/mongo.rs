@@ -0,0 +1,280 @@
+use db_test_model::DataRow;
+
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+pub mod insert_bulk;
+mod mongosh;
+
+pub use insert_bulk::{
+    Defaults, IndexAfterLoad, IndexBuild, InsertMany, Journaled, Strategy, Unacknowledged,
+};
+
+/// MongoDB keeping one document per transaction, inserted as `V` does.
+///
+/// Only the exec load mode is supported, the loads run through the tools
+/// shipped in the image.
+pub struct Mongo<V: Variant = Defaults> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+/// Insert strategy, write concern and index build benchmarked as a backend
+/// of its own.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    const STRATEGY: Strategy;
+    /// Write concern document of every insert, e.g. `{w:1}`
+    const WRITE_CONCERN: &str;
+    /// Whether [`Variant::WRITE_CONCERN`] waits for the server to apply the inserts
+    const ACKNOWLEDGED: bool;
+    const INDEX_BUILD: IndexBuild;
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use Mongo as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = "mongo";
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some("/data/db");
+    const ARTIFACTS: &'static [Artifact] = &[
+        Artifact::Logs,
+        Artifact::Command {
+            name: "server-status",
+            script: "mongosh --quiet --norc --eval 'printjson(db.serverStatus())'",
+        },
+        Artifact::Command {
+            name: "collection-stats",
+            script: "mongosh --quiet --norc bench \
+                     --eval 'printjson(db.user_transactions.stats())'",
+        },
+        Artifact::Command {
+            name: "data-dir",
+            script: "du -ab /data/db",
+        },
+    ];
+
+    /// Unacknowledged loads only know how many documents they sent, the
+    /// stored ones are counted by [`Backend::verify`](crate::Backend::verify).
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        let mut loader = mongosh::Mongosh::parse_load_output(output)?;
+        if !V::ACKNOWLEDGED {
+            loader.rows_loaded = None;
+        }
+        Ok(loader)
+    }
+
+    /// Compressed size of the collection with its index, and the WiredTiger
+    /// cache holding them.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let mongosh = mongosh::Mongosh::new(config, container_name);
+        let js = "const stats = db.runCommand({dbStats: 1}); \
+                  const cache = db.serverStatus().wiredTiger.cache; \
+                  print(stats.storageSize + stats.indexSize, \
+                  cache[\"bytes currently in the cache\"])";
+        let output = mongosh.eval(js, Phase::Footprint).await?;
+        let sizes = output.split_whitespace().collect::<Vec<_>>();
+        let [disk_bytes, memory_bytes] = sizes[..] else {
+            anyhow::bail!("invalid sizes: {output:?}");
+        };
+        Ok(Footprint {
+            memory_bytes: Some(memory_bytes.parse()?),
+            disk_bytes: Some(disk_bytes.parse()?),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+    const LOAD_MODES: &'static [crate::LoadMode] = &[crate::LoadMode::Exec];
+
+    type Instance = crate::docker::Instance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<crate::docker::Instance> {
+        crate::docker::ensure_exec_load(V::NAME, &self.config, input)?;
+        self.containers_pool.provision().await
+    }
+
+    async fn ready(&self, instance: &mut crate::docker::Instance) -> anyhow::Result<()> {
+        let mongosh = mongosh::Mongosh::new(&self.config, &instance.container_name);
+        mongosh.wait_ready().await
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut crate::docker::Instance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let mongosh = mongosh::Mongosh::new(&self.config, &instance.container_name);
+        mongosh.migrate(V::INDEX_BUILD).await?;
+        instance.load = Some(insert_bulk::prepare::<V>(&mongosh, input).await?);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: crate::docker::Instance) -> anyhow::Result<Self::Bencher> {
+        crate::docker::Bench::from_instance(self.config.clone(), instance)
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every transaction is stored once with its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let mongosh = &mongosh::Mongosh::new(&self.config, container_name);
+        let counts = async {
+            if !V::ACKNOWLEDGED {
+                mongosh.wait_settled(Phase::Verify).await?;
+            }
+            let js = "const users = db.user_transactions.aggregate([\
+                      {$group: {_id: \"$user_addr\"}}, {$count: \"users\"}]).toArray(); \
+                      print(db.user_transactions.countDocuments(), \
+                      users.length ? users[0].users : 0)";
+            let counts = mongosh.eval(js, Phase::Verify).await?;
+            let (rows, users) = counts
+                .trim()
+                .split_once(' ')
+                .ok_or(anyhow::anyhow!("invalid counts: {counts:?}"))?;
+            Ok((rows.parse()?, users.parse()?))
+        };
+        let trans_time = |row: &DataRow| {
+            let js = format!(
+                "const doc = db.user_transactions.findOne({{user_addr: {}, trans_hash: {}}}); \
+                 print(doc ? String(doc.trans_time) : \"missing\")",
+                db_test_model::json_string(&row.user),
+                db_test_model::json_string(&row.transaction_id)
+            );
+            async move { Ok(mongosh.eval(&js, Phase::Verify).await?.trim().to_owned()) }
+        };
+        verify::stored_rows(input, &outcome.loader, counts, trans_time).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mongosh::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::{ExecOutput, Runtime};
+    use crate::testing::stdout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let output = |stdout: String, stderr: &str| ExecOutput {
+            stdout,
+            stderr: stderr.into(),
+            ..Default::default()
+        };
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("mongo_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = db_test_model::sample_data_file(&bench_input.file_path, 10)?;
+        let summary = "2026-10-19T00:00:00.000+0000\tconnected to: mongodb://127.0.0.1:27017/bench\n\
+                       2026-10-19T00:00:00.000+0000\t10 document(s) imported successfully. \
+                       0 document(s) failed to import.\n";
+        fake.script_exec("mongoimport", output(String::new(), summary))
+            .script_exec(
+                "countDocuments",
+                output(format!("10 {}\n", expected.users), ""),
+            )
+            .script_exec("dbStats", output("65536 131072\n".into(), ""));
+        for row in &expected.sample {
+            let lookup = format!("trans_hash: \"{}\"", row.transaction_id);
+            fake.script_exec(&lookup, output(format!("{}\n", row.timestamp), ""));
+        }
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mongo = Mongo::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(65536));
+        assert_eq!(footprint.memory_bytes, Some(131072));
+
+        // A missing document fails the verification
+        fake.script_exec(
+            "countDocuments",
+            output(format!("9 {}\n", expected.users), ""),
+        );
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unacknowledged_verify_waits_for_inserts() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("mongo_unacknowledged_verify", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = db_test_model::sample_data_file(&bench_input.file_path, 10)?;
+        let summary = "2026-10-19T00:00:00.000+0000\t10 document(s) imported successfully. \
+                       0 document(s) failed to import.\n";
+        let summary = ExecOutput {
+            stderr: summary.into(),
+            ..Default::default()
+        };
+        fake.script_exec("mongoimport", summary)
+            .script_exec(
+                "countDocuments(",
+                stdout(format!("10 {}\n", expected.users)),
+            )
+            .script_exec("countDocuments({})", stdout("10\n"));
+        for row in &expected.sample {
+            let lookup = format!("trans_hash: \"{}\"", row.transaction_id);
+            fake.script_exec(&lookup, stdout(format!("{}\n", row.timestamp)));
+        }
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mongo<Unacknowledged> = Mongo::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        assert_eq!(outcome.loader.rows_loaded, None);
+        backend.verify(&bench_input, &outcome).await?;
+        // Counted until the count is the same twice
+        let settled = crate::testing::execs(&fake)
+            .into_iter()
+            .filter(|exec| exec.contains("countDocuments({})"))
+            .count();
+        assert_eq!(settled, 2);
+        Ok(())
+    }
+}
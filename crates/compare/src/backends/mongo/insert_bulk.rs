@@ -0,0 +1,260 @@
+use db_test_model::temp::{CsvFilesManager, JsonLinesFilesManager};
+
+use super::mongosh::{CREATE_INDEX, Mongosh};
+use super::{Container, Variant};
+use crate::docker::{Docker, Load};
+use crate::workload::WorkloadInput;
+
+/// Documents per `mongoimport` batch or `insertMany` call.
+pub const BATCH_SIZE_ENV: &str = "DB_TEST_MONGO_BATCH_SIZE";
+
+/// How the measured load writes the documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// `mongoimport` of the JSON Lines documents
+    Import,
+    /// `mongosh` script of `insertMany` calls
+    InsertMany,
+}
+
+/// When the index is built relative to the measured load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexBuild {
+    /// Maintained by every insert
+    BeforeLoad,
+    /// Built once the documents are in, as part of the measured load
+    AfterLoad,
+}
+
+macro_rules! variant {
+    ($(#[$doc:meta])* $variant:ident, $name:literal, $strategy:expr, $write_concern:literal, $acknowledged:literal, $index_build:expr) => {
+        $(#[$doc])*
+        pub struct $variant;
+
+        impl Variant for $variant {
+            const NAME: &'static str = $name;
+            const CONTAINER_NAME_PREFIX: &'static str = concat!("bench-", $name);
+            const STRATEGY: Strategy = $strategy;
+            const WRITE_CONCERN: &'static str = $write_concern;
+            const ACKNOWLEDGED: bool = $acknowledged;
+            const INDEX_BUILD: IndexBuild = $index_build;
+        }
+    };
+}
+
+variant!(
+    /// `mongoimport` into the indexed collection, acknowledged writes
+    Defaults,
+    "mongo",
+    Strategy::Import,
+    "{w:1}",
+    true,
+    IndexBuild::BeforeLoad
+);
+variant!(
+    IndexAfterLoad,
+    "mongo-index-after",
+    Strategy::Import,
+    "{w:1}",
+    true,
+    IndexBuild::AfterLoad
+);
+variant!(
+    /// Inserts return as soon as they are sent, see [`Variant::ACKNOWLEDGED`]
+    Unacknowledged,
+    "mongo-unacknowledged",
+    Strategy::Import,
+    "{w:0}",
+    false,
+    IndexBuild::BeforeLoad
+);
+variant!(
+    /// Every batch waits for the journal
+    Journaled,
+    "mongo-journaled",
+    Strategy::Import,
+    "{w:1,j:true}",
+    true,
+    IndexBuild::BeforeLoad
+);
+variant!(
+    InsertMany,
+    "mongo-insert-many",
+    Strategy::InsertMany,
+    "{w:1}",
+    true,
+    IndexBuild::BeforeLoad
+);
+
+/// Measured load writing every document of the uploaded file with the
+/// strategy and write concern of `V`, building the index at the end when
+/// it is built after the load.
+pub(super) async fn prepare<V: Variant>(
+    mongosh: &Mongosh<'_>,
+    input: &WorkloadInput,
+) -> anyhow::Result<Load> {
+    let batch_size = crate::workload::batch_size_from_env(BATCH_SIZE_ENV)?;
+
+    // Upload data file
+    let config = mongosh.config();
+    let container_name = mongosh.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = match V::STRATEGY {
+        Strategy::Import => JsonLinesFilesManager::tar_data_file(&input.file_path, dst_file)?,
+        Strategy::InsertMany => {
+            let (documents, _) = JsonLinesFilesManager::encode_data_file(&input.file_path)?;
+            let encoded = encode::<V>(&documents, batch_size)?;
+            let stem = input.file_path.file_stem().unwrap_or_default();
+            let name = format!("{}-{}", stem.display(), V::NAME);
+            CsvFilesManager::tar_encoded(&input.file_path, &name, &encoded, dst_file)?
+        }
+    };
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
+
+    // Prepare load exec
+    let command = match V::STRATEGY {
+        Strategy::Import => {
+            let mut script = format!(
+                "mongoimport --uri {} --collection user_transactions --type json \
+                 --file {data_file} --batchSize {batch_size} --writeConcern '{}'",
+                Mongosh::URI,
+                V::WRITE_CONCERN
+            );
+            if V::INDEX_BUILD == IndexBuild::AfterLoad {
+                let mongosh = Mongosh::command(&[]).join(" ");
+                script = format!("{script} && {mongosh} --eval '{CREATE_INDEX}'");
+            }
+            vec!["bash".to_owned(), "-c".to_owned(), script]
+        }
+        Strategy::InsertMany => Mongosh::command(&["--file", &data_file])
+            .into_iter()
+            .map(str::to_owned)
+            .collect(),
+    };
+    let command = command.iter().map(String::as_str).collect();
+    let exec_id = Container::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
+}
+
+/// Script inserting `batch_size` of the JSON Lines `documents` per
+/// `insertMany` call, then printing how many were inserted.
+///
+/// The documents are Extended JSON, deserialized in canonical mode so that
+/// every field is stored with the BSON type `mongoimport` gives it.
+fn encode<V: Variant>(documents: &[u8], batch_size: usize) -> anyhow::Result<Vec<u8>> {
+    let documents = std::str::from_utf8(documents)?.lines().collect::<Vec<_>>();
+    let mut encoded = b"let rows = 0;\n".to_vec();
+    for batch in documents.chunks(batch_size) {
+        let insert = format!(
+            "rows += Object.keys(db.user_transactions.insertMany(\
+             EJSON.deserialize([{}], {{relaxed: false}}), \
+             {{writeConcern: {}}}).insertedIds).length;\n",
+            batch.join(", "),
+            V::WRITE_CONCERN
+        );
+        encoded.extend_from_slice(insert.as_bytes());
+    }
+    if V::INDEX_BUILD == IndexBuild::AfterLoad {
+        encoded.extend_from_slice(format!("{CREATE_INDEX};\n").as_bytes());
+    }
+    encoded.extend_from_slice(b"print(rows);\n");
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Mongo;
+    use super::super::mongosh::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_creates_index_and_uploads_documents() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mongo = Mongo::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("mongo_prepare_uploads_documents", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-mongo-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert_eq!(
+            execs[1],
+            format!(
+                "mongosh --quiet --norc mongodb://127.0.0.1:27017/bench \
+                 --eval db.createCollection(\"user_transactions\"); {CREATE_INDEX}"
+            )
+        );
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "bash -c mongoimport --uri mongodb://127.0.0.1:27017/bench \
+                 --collection user_transactions --type json --file /tmp/items \
+                 --batchSize 1000 --writeConcern '{w:1}'"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn index_after_load_is_part_of_the_load() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mongo<IndexAfterLoad> = Mongo::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("mongo_index_after_load", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let execs = crate::testing::execs(&fake);
+        assert!(!execs[1].contains("createIndex"));
+        assert!(execs.last().is_some_and(|load| load.ends_with(&format!(
+            "--writeConcern '{{w:1}}' && mongosh --quiet --norc \
+             mongodb://127.0.0.1:27017/bench --eval '{CREATE_INDEX}'"
+        ))));
+        Ok(())
+    }
+
+    #[test]
+    fn documents_keep_timestamps_as_longs() -> anyhow::Result<()> {
+        let file_path = crate::testing::gen_test_csv("mongo_documents_keep_longs", 1)?;
+        let (documents, count) = JsonLinesFilesManager::encode_data_file(&file_path)?;
+        let row = &db_test_model::read_data_file(&file_path)?[0];
+        assert_eq!(count, 1);
+        assert_eq!(
+            String::from_utf8(documents)?,
+            format!(
+                "{{\"user_addr\":\"{}\",\"trans_time\":{{\"$numberLong\":\"{}\"}},\"trans_hash\":\"{}\"}}\n",
+                row.user, row.timestamp, row.transaction_id
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encode_batches_in_insert_many_calls() -> anyhow::Result<()> {
+        let documents = b"{\"a\":1}\n{\"b\":2}\n{\"c\":3}\n";
+        let encoded = String::from_utf8(encode::<InsertMany>(documents, 2)?)?;
+        assert_eq!(
+            encoded,
+            "let rows = 0;\n\
+             rows += Object.keys(db.user_transactions.insertMany(\
+             EJSON.deserialize([{\"a\":1}, {\"b\":2}], {relaxed: false}), \
+             {writeConcern: {w:1}}).insertedIds).length;\n\
+             rows += Object.keys(db.user_transactions.insertMany(\
+             EJSON.deserialize([{\"c\":3}], {relaxed: false}), \
+             {writeConcern: {w:1}}).insertedIds).length;\n\
+             print(rows);\n"
+        );
+        let encoded = String::from_utf8(encode::<IndexAfterLoad>(documents, 3)?)?;
+        assert!(encoded.ends_with(&format!("{CREATE_INDEX};\nprint(rows);\n")));
+        Ok(())
+    }
+}
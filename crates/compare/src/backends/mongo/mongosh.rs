@@ -0,0 +1,193 @@
+use super::{Container, IndexBuild};
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// Index of the queries by user in time order, the documents are looked up
+/// by hash only when verified.
+pub(super) const CREATE_INDEX: &str = "db.user_transactions.createIndex(\
+     {user_addr: 1, trans_time: 1}, {name: \"transactions_index\"})";
+
+/// Runs JavaScript through `mongosh` inside a started container.
+pub(super) struct Mongosh<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+}
+
+impl<'a> Mongosh<'a> {
+    /// Database the collection is created in.
+    pub(super) const URI: &'static str = "mongodb://127.0.0.1:27017/bench";
+    const READY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+    /// Unacknowledged inserts stream in fast, a count unchanged for this
+    /// long means the server is done with them.
+    const SETTLE_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
+    pub(super) fn new(config: &'a crate::BackendConfig, container_name: &'a str) -> Self {
+        Mongosh {
+            config,
+            container_name,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// `mongosh` connected to the database, printing only what the script
+    /// prints.
+    pub(super) fn command<'c>(args: &[&'c str]) -> Vec<&'c str> {
+        let mut cmd = vec!["mongosh", "--quiet", "--norc", Self::URI];
+        cmd.extend(args);
+        cmd
+    }
+
+    /// Evaluates `js` as part of `phase`, returning what it prints.
+    pub(super) async fn eval(&self, js: &str, phase: Phase) -> anyhow::Result<String> {
+        let cmd = Self::command(&["--eval", js]);
+        let output = Container::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// `mongosh` reports errors on stderr, `mongoimport` logs its fatal
+    /// ones as `Failed:` next to its progress.
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        let error = output
+            .stderr
+            .lines()
+            .find(|line| line.contains("Error") || line.contains("\tFailed: "));
+        if let Some(error) = error {
+            anyhow::bail!("mongo tools failed: {}", error.trim());
+        }
+        Ok(())
+    }
+
+    /// Waits until the server answers pings.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let cmd = Self::command(&["--eval", "db.runCommand({ping: 1}).ok"]);
+            let output = Container::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.trim() == "1" {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Waits until the count of documents stops changing, as the server is
+    /// still applying unacknowledged inserts once the load returned.
+    pub(super) async fn wait_settled(&self, phase: Phase) -> anyhow::Result<()> {
+        let mut count = None;
+        loop {
+            let js = "print(db.user_transactions.countDocuments({}))";
+            let current = self.eval(js, phase).await?.trim().parse::<u64>()?;
+            if count == Some(current) {
+                return Ok(());
+            }
+            count = Some(current);
+            tokio::time::sleep(Self::SETTLE_POLL).await;
+        }
+    }
+
+    /// Creates the collection, and its index unless it is built by the load.
+    pub(super) async fn migrate(&self, index_build: IndexBuild) -> anyhow::Result<()> {
+        let mut js = vec!["db.createCollection(\"user_transactions\")"];
+        if index_build == IndexBuild::BeforeLoad {
+            js.push(CREATE_INDEX);
+        }
+        self.eval(&js.join("; "), Phase::LoadFixture)
+            .await
+            .map_err(|err| anyhow::anyhow!("collection creation failed: {err}"))?;
+        Ok(())
+    }
+
+    /// `mongoimport` ends with an `N document(s) imported successfully.
+    /// M document(s) failed to import.` summary, the `insertMany` scripts
+    /// print how many documents they inserted.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        Self::ensure_no_error(output)?;
+        let summary = output
+            .stderr
+            .lines()
+            .rev()
+            .find_map(|line| line.split_once(" document(s) imported successfully. "));
+        if let Some((imported, failed)) = summary {
+            let imported = imported.split_whitespace().next_back().unwrap_or_default();
+            let (failed, _) = failed
+                .split_once(" document(s) failed")
+                .ok_or(anyhow::anyhow!("invalid mongoimport summary: {failed}"))?;
+            return Ok(LoaderReport {
+                rows_loaded: Some(imported.parse()?),
+                errors: failed.parse()?,
+                ..Default::default()
+            });
+        }
+        let rows_loaded = output
+            .stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .ok_or(anyhow::anyhow!(
+                "no document count in load output: {}",
+                output.stderr
+            ))?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded.parse()?),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use crate::runtime::FakeRuntime;
+
+    /// Fake runtime whose servers are ready right away.
+    pub(in crate::backends::mongo) fn ready_fake() -> FakeRuntime {
+        crate::testing::ready_fake("ping: 1", "1\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let output = ExecOutput {
+            stdout: String::new(),
+            stderr: "2026-10-19T00:00:00.000+0000\tconnected to: mongodb://127.0.0.1:27017/bench\n\
+                     2026-10-19T00:00:00.000+0000\t498 document(s) imported successfully. \
+                     2 document(s) failed to import.\n"
+                .into(),
+            ..Default::default()
+        };
+        let loader = Mongosh::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(498));
+        assert_eq!(loader.errors, 2);
+
+        let output = ExecOutput {
+            stdout: "500\n".into(),
+            ..Default::default()
+        };
+        assert_eq!(Mongosh::parse_load_output(&output)?.rows_loaded, Some(500));
+
+        let output = ExecOutput {
+            stdout: String::new(),
+            stderr: "2026-10-19T00:00:00.000+0000\tFailed: open /tmp/items: \
+                     no such file or directory\n"
+                .into(),
+            ..Default::default()
+        };
+        assert!(Mongosh::parse_load_output(&output).is_err());
+        assert!(Mongosh::parse_load_output(&ExecOutput::default()).is_err());
+        Ok(())
+    }
+}
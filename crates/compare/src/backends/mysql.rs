@@ -0,0 +1,237 @@
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod cli;
+pub mod insert_bulk;
+
+pub use insert_bulk::{LoadData, MariaDbLoadData, MariaDbValuesBatches, Strategy, ValuesBatches};
+
+/// MySQL compatible server keeping the transactions in an InnoDB table,
+/// inserting them as `V` does.
+///
+/// Only the exec load mode is supported, the loads run through the client
+/// shipped in the image.
+pub struct Mysql<V: Variant = LoadData> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+/// Engine and insert strategy benchmarked as a backend of its own.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    const ENGINE: Engine;
+    const STRATEGY: Strategy;
+}
+
+/// MySQL compatible server image, with the binaries it ships.
+#[derive(Clone, Copy)]
+pub struct Engine {
+    pub image_name: &'static str,
+    pub server: &'static str,
+    pub client: &'static str,
+    pub admin: &'static str,
+    /// Allows root without a password and creates the database
+    pub env: &'static [&'static str],
+    pub artifacts: &'static [Artifact],
+}
+
+macro_rules! engine {
+    ($image_name:literal, $server:literal, $client:literal, $admin:literal, [$($env:literal),*]) => {
+        Engine {
+            image_name: $image_name,
+            server: $server,
+            client: $client,
+            admin: $admin,
+            env: &[$($env),*],
+            artifacts: &[
+                Artifact::Logs,
+                Artifact::Command {
+                    name: "variables",
+                    script: concat!($client, " -u root -e 'SHOW GLOBAL VARIABLES'"),
+                },
+                Artifact::Command {
+                    name: "tables",
+                    script: concat!($client, " -u root -e 'SHOW TABLE STATUS' bench"),
+                },
+                Artifact::Command {
+                    name: "data-dir",
+                    script: "du -ab /var/lib/mysql",
+                },
+            ],
+        }
+    };
+}
+
+impl Engine {
+    pub const MYSQL: Engine = engine!(
+        "mysql",
+        "mysqld",
+        "mysql",
+        "mysqladmin",
+        ["MYSQL_ALLOW_EMPTY_PASSWORD=yes", "MYSQL_DATABASE=bench"]
+    );
+    pub const MARIADB: Engine = engine!(
+        "mariadb",
+        "mariadbd",
+        "mariadb",
+        "mariadb-admin",
+        [
+            "MARIADB_ALLOW_EMPTY_ROOT_PASSWORD=yes",
+            "MARIADB_DATABASE=bench"
+        ]
+    );
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use Mysql as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = V::ENGINE.image_name;
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some("/var/lib/mysql");
+    const ARTIFACTS: &'static [Artifact] = V::ENGINE.artifacts;
+
+    /// Lets `LOAD DATA INFILE` read the uploaded data file.
+    fn container_spec() -> ContainerSpec {
+        let spec = ContainerSpec::new(Self::IMAGE_NAME);
+        let spec = V::ENGINE.env.iter().fold(spec, |spec, var| spec.env(var));
+        spec.cmd([V::ENGINE.server, "--secure-file-priv=/tmp"])
+    }
+
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        cli::Cli::parse_load_output(output)
+    }
+
+    /// Size of the table together with its index, as of fresh statistics.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let cli = cli::Cli::new(config, container_name, V::ENGINE);
+        let sql = "analyze table user_transactions; \
+                   select data_length + index_length from information_schema.tables \
+                   where table_schema = database() and table_name = 'user_transactions'";
+        let output = cli.query(sql, Phase::Footprint).await?;
+        let disk_bytes = output
+            .lines()
+            .next_back()
+            .ok_or(anyhow::anyhow!("no table size in: {output:?}"))?;
+        Ok(Footprint {
+            memory_bytes: None,
+            disk_bytes: Some(disk_bytes.trim().parse()?),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+    const LOAD_MODES: &'static [crate::LoadMode] = &[crate::LoadMode::Exec];
+
+    type Instance = crate::docker::Instance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<crate::docker::Instance> {
+        crate::docker::ensure_exec_load(V::NAME, &self.config, input)?;
+        self.containers_pool.provision().await
+    }
+
+    async fn ready(&self, instance: &mut crate::docker::Instance) -> anyhow::Result<()> {
+        let cli = cli::Cli::new(&self.config, &instance.container_name, V::ENGINE);
+        cli.wait_ready().await
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut crate::docker::Instance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let cli = cli::Cli::new(&self.config, &instance.container_name, V::ENGINE);
+        cli.migrate().await?;
+        instance.load = Some(insert_bulk::prepare(&cli, input, V::STRATEGY).await?);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: crate::docker::Instance) -> anyhow::Result<Self::Bencher> {
+        crate::docker::Bench::from_instance(self.config.clone(), instance)
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is stored once with its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let cli = &cli::Cli::new(&self.config, container_name, V::ENGINE);
+        let query = |sql: String| async move { cli.query(&sql, Phase::Verify).await };
+        verify::sql_rows(input, &outcome.loader, '\t', query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cli::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::Runtime;
+    use crate::testing::stdout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("mysql_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = crate::testing::script_sql_rows(&fake, &bench_input, '\t')?;
+        fake.script_exec("LOAD DATA INFILE", stdout("10\n"))
+            .script_exec(
+                "index_length",
+                stdout("bench.user_transactions\tanalyze\tstatus\tOK\n16384\n"),
+            );
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mysql = Mysql::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(16384));
+
+        // A missing row fails the verification
+        fake.script_exec("count(*)", stdout(format!("9\t{}\n", expected.users)));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use super::{Container, Engine};
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// Columns of the Postgres migrations with bounded types, InnoDB indexes no
+/// `text` column without a prefix length. Without a primary key the rows are
+/// clustered on a hidden row id, so the secondary index carries the hash
+/// for the lookups to stay within it.
+const SCHEMA: &str = "\
+create table if not exists user_transactions (
+    user_addr varchar(64) not null,
+    trans_time bigint not null,
+    trans_hash char(64) not null,
+    index transactions_index (user_addr, trans_time, trans_hash)
+) engine = InnoDB";
+
+/// Runs SQL through the client of `engine` inside a started container.
+pub(super) struct Cli<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+    engine: Engine,
+}
+
+impl<'a> Cli<'a> {
+    const USER: &'static str = "root";
+    /// Created by the image from its environment.
+    const DATABASE: &'static str = "bench";
+    /// Printed by `mysqladmin ping` once the server accepts clients.
+    const ALIVE: &'static str = "is alive";
+    const READY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    pub(super) fn new(
+        config: &'a crate::BackendConfig,
+        container_name: &'a str,
+        engine: Engine,
+    ) -> Self {
+        Cli {
+            config,
+            container_name,
+            engine,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// Client running `sql` on the database, printing tab separated rows
+    /// without headers.
+    pub(super) fn command<'c>(&self, sql: &'c str) -> Vec<&'c str> {
+        let client = self.engine.client;
+        vec![
+            client,
+            "-u",
+            Self::USER,
+            "-N",
+            "-B",
+            "-e",
+            sql,
+            Self::DATABASE,
+        ]
+    }
+
+    /// Runs `sql` as part of `phase`, returning its rows.
+    pub(super) async fn query(&self, sql: &str, phase: Phase) -> anyhow::Result<String> {
+        let cmd = self.command(sql);
+        let output = Container::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// The client also warns on stderr, only its errors fail the statements.
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !output.stderr.contains("ERROR"),
+            "client failed: {}",
+            output.stderr.trim()
+        );
+        Ok(())
+    }
+
+    /// Waits until the server accepts TCP clients.
+    ///
+    /// The image initializes the database with a temporary server which
+    /// does not listen on TCP, so the socket alone does not tell it is up.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let admin = self.engine.admin;
+            let cmd = vec![admin, "-u", Self::USER, "-h", "127.0.0.1", "ping"];
+            let output = Container::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.contains(Self::ALIVE) {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Creates the table.
+    pub(super) async fn migrate(&self) -> anyhow::Result<()> {
+        self.query(SCHEMA, Phase::LoadFixture)
+            .await
+            .map_err(|err| anyhow::anyhow!("schema creation failed: {err}"))?;
+        Ok(())
+    }
+
+    /// Rows inserted by the load, printed by its last statement.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        Self::ensure_no_error(output)?;
+        let rows_loaded = output
+            .stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .ok_or(anyhow::anyhow!(
+                "no row count in client output: {}",
+                output.stderr
+            ))?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded.parse()?),
+            ..Default::default()
+        })
+    }
+}
+
+/// Quotes `value` as a MySQL string literal, which reads backslashes as escapes.
+pub(super) fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use crate::runtime::FakeRuntime;
+
+    /// Fake runtime whose servers are ready right away.
+    pub(in crate::backends::mysql) fn ready_fake() -> FakeRuntime {
+        crate::testing::ready_fake(" ping", "mysqld is alive\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let output = ExecOutput {
+            stdout: "500\n".into(),
+            ..Default::default()
+        };
+        let loader = Cli::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(500));
+
+        let output = ExecOutput {
+            stdout: String::new(),
+            stderr: "ERROR 1290 (HY000) at line 1: The MySQL server is running with the \
+                     --secure-file-priv option so it cannot execute this statement\n"
+                .into(),
+            ..Default::default()
+        };
+        assert!(Cli::parse_load_output(&output).is_err());
+        assert_eq!(literal("it's"), "'it''s'");
+        assert_eq!(literal("a\\"), "'a\\\\'");
+        Ok(())
+    }
+}
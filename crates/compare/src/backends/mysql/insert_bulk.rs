@@ -0,0 +1,222 @@
+use db_test_model::DataRow;
+use db_test_model::temp::CsvFilesManager;
+
+use super::cli::{Cli, literal};
+use super::{Container, Engine, Variant};
+use crate::docker::{Docker, Load};
+use crate::workload::WorkloadInput;
+
+/// Rows per statement of the batched strategy.
+pub const BATCH_SIZE_ENV: &str = "DB_TEST_MYSQL_BATCH_SIZE";
+
+/// How the measured load writes the dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// One `LOAD DATA INFILE` of the whole dataset
+    LoadData,
+    /// Multi-row `INSERT … VALUES` statements
+    InsertValues,
+}
+
+impl Strategy {
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::LoadData => "load-data",
+            Strategy::InsertValues => "insert-values",
+        }
+    }
+}
+
+macro_rules! variant {
+    ($(#[$doc:meta])* $variant:ident, $name:literal, $engine:expr, $strategy:expr) => {
+        $(#[$doc])*
+        pub struct $variant;
+
+        impl Variant for $variant {
+            const NAME: &'static str = $name;
+            const CONTAINER_NAME_PREFIX: &'static str = concat!("bench-", $name);
+            const ENGINE: Engine = $engine;
+            const STRATEGY: Strategy = $strategy;
+        }
+    };
+}
+
+variant!(
+    /// `LOAD DATA INFILE` the data file itself
+    LoadData,
+    "mysql",
+    Engine::MYSQL,
+    Strategy::LoadData
+);
+variant!(
+    ValuesBatches,
+    "mysql-insert-values",
+    Engine::MYSQL,
+    Strategy::InsertValues
+);
+variant!(
+    MariaDbLoadData,
+    "mariadb",
+    Engine::MARIADB,
+    Strategy::LoadData
+);
+variant!(
+    MariaDbValuesBatches,
+    "mariadb-insert-values",
+    Engine::MARIADB,
+    Strategy::InsertValues
+);
+
+/// Measured load writing the whole dataset with `strategy` from the
+/// uploaded file, then printing how many rows were inserted.
+pub(super) async fn prepare(
+    cli: &Cli<'_>,
+    input: &WorkloadInput,
+    strategy: Strategy,
+) -> anyhow::Result<Load> {
+    // Upload data file
+    let config = cli.config();
+    let container_name = cli.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = match strategy {
+        Strategy::LoadData => CsvFilesManager::tar_data_file(&input.file_path, dst_file)?,
+        Strategy::InsertValues => {
+            let rows = db_test_model::read_data_file(&input.file_path)?;
+            let encoded = encode(&rows, crate::workload::batch_size_from_env(BATCH_SIZE_ENV)?)?;
+            let stem = input.file_path.file_stem().unwrap_or_default();
+            let name = format!("{}-mysql-{}", stem.display(), strategy.name());
+            CsvFilesManager::tar_encoded(&input.file_path, &name, &encoded, dst_file)?
+        }
+    };
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
+
+    // Prepare load exec
+    let sql = match strategy {
+        Strategy::LoadData => format!(
+            "LOAD DATA INFILE {} INTO TABLE user_transactions \
+             FIELDS TERMINATED BY ',' (user_addr, trans_time, trans_hash); \
+             select row_count()",
+            literal(&data_file)
+        ),
+        Strategy::InsertValues => format!("source {data_file}"),
+    };
+    let command = cli.command(&sql);
+    let exec_id = Container::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
+}
+
+/// Script inserting `batch_size` rows per statement, then printing how many
+/// were inserted.
+fn encode(rows: &[DataRow], batch_size: usize) -> anyhow::Result<Vec<u8>> {
+    let mut encoded = b"set @rows = 0;\n".to_vec();
+    for batch in rows.chunks(batch_size) {
+        let mut values = vec![];
+        for row in batch {
+            let timestamp: i64 = row
+                .timestamp
+                .parse()
+                .map_err(|err| anyhow::anyhow!("invalid timestamp of {row:?}: {err}"))?;
+            values.push(format!(
+                "({}, {timestamp}, {})",
+                literal(&row.user),
+                literal(&row.transaction_id)
+            ));
+        }
+        let insert = format!(
+            "insert into user_transactions (user_addr, trans_time, trans_hash) values {};\n\
+             set @rows = @rows + row_count();\n",
+            values.join(", ")
+        );
+        encoded.extend_from_slice(insert.as_bytes());
+    }
+    encoded.extend_from_slice(b"select @rows;\n");
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Mysql;
+    use super::super::cli::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{ExecOutput, Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_creates_table_and_uploads_dataset() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        fake.script_exec(
+            "LOAD DATA INFILE",
+            ExecOutput {
+                stdout: "10\n".into(),
+                ..Default::default()
+            },
+        );
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mysql = Mysql::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("mysql_prepare_uploads_dataset", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-mysql-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert_eq!(execs[0], "mysqladmin -u root -h 127.0.0.1 ping");
+        assert!(execs[1].starts_with("mysql -u root -N -B -e create table"));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "mysql -u root -N -B -e LOAD DATA INFILE '/tmp/items' \
+                 INTO TABLE user_transactions FIELDS TERMINATED BY ',' \
+                 (user_addr, trans_time, trans_hash); select row_count() bench"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
+
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn values_batches_source_the_script() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Mysql<MariaDbValuesBatches> = Mysql::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("mysql_values_batches", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let execs = crate::testing::execs(&fake);
+        assert_eq!(execs[0], "mariadb-admin -u root -h 127.0.0.1 ping");
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some("mariadb -u root -N -B -e source /tmp/items bench")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encode_batches_in_statements() -> anyhow::Result<()> {
+        let row = |user: &str, timestamp: &str| DataRow {
+            user: user.into(),
+            timestamp: timestamp.into(),
+            transaction_id: format!("{user}{timestamp}"),
+        };
+        let rows = [row("a", "1"), row("b", "2"), row("c", "3")];
+        let encoded = String::from_utf8(encode(&rows, 2)?)?;
+        assert_eq!(
+            encoded,
+            "set @rows = 0;\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) values ('a', 1, 'a1'), ('b', 2, 'b2');\n\
+             set @rows = @rows + row_count();\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) values ('c', 3, 'c3');\n\
+             set @rows = @rows + row_count();\n\
+             select @rows;\n"
+        );
+        Ok(())
+    }
+}
@@ -1 +1,225 @@
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod client;
 pub mod insert_bulk;
+mod psql;
+
+pub use insert_bulk::{
+    CopyBinary, CopyCsv, CopyText, PreparedBatches, Strategy, UnnestBatches, ValuesBatches,
+};
+
+/// Postgres keeping the transactions in the `user_transactions` table,
+/// created by the embedded migrations, and inserting them as `V` does.
+pub struct Postgres<V: Variant = CopyCsv> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+/// Insert strategy benchmarked as a backend of its own.
+///
+/// The strategy applies to exec and host loads, driver loads insert the rows
+/// one by one whatever the variant.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    const STRATEGY: Strategy;
+}
+
+/// Started container, with the measured load once the fixture is loaded.
+pub struct PostgresInstance {
+    container_name: Box<str>,
+    container_guard: crate::docker::ContainerGuard,
+    /// Published client port, for loads connecting from the host
+    addr: Option<std::net::SocketAddr>,
+    load: Option<crate::docker::Load>,
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use Postgres as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = "postgres";
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some("/var/lib/postgresql/data");
+    const CLIENT_PORT: Option<u16> = Some(5432);
+    const ARTIFACTS: &'static [Artifact] = &[
+        Artifact::Logs,
+        Artifact::Command {
+            name: "settings",
+            script: "psql -U postgres -c 'SHOW ALL'",
+        },
+        Artifact::Command {
+            name: "relations",
+            script: "psql -U postgres -c '\\dt+' -c '\\di+'",
+        },
+        Artifact::Command {
+            name: "data-dir",
+            script: "du -ab /var/lib/postgresql/data",
+        },
+    ];
+
+    fn container_spec() -> ContainerSpec {
+        ContainerSpec::new(Self::IMAGE_NAME).env("POSTGRES_HOST_AUTH_METHOD=trust")
+    }
+
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        psql::Psql::parse_load_output(output)
+    }
+
+    /// Size of the table together with its index, leaving out the WAL and
+    /// the system catalogs.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let psql = psql::Psql::new(config, container_name, None);
+        let sql = "select pg_total_relation_size('user_transactions')";
+        let disk_bytes = psql.query(sql, Phase::Footprint).await?;
+        Ok(Footprint {
+            memory_bytes: None,
+            disk_bytes: Some(disk_bytes.trim().parse()?),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+
+    type Instance = PostgresInstance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<PostgresInstance> {
+        anyhow::ensure!(
+            input.clients == 1 || self.config.load_mode == crate::LoadMode::Driver,
+            "concurrent clients need the driver load mode"
+        );
+        let container_info = self.containers_pool.create_container().await?;
+        let crate::docker::ContainerInfo { container_name, .. } = container_info;
+        let container_guard = {
+            let container_name = container_name.clone();
+            Self::start_container(&self.config, container_name).await?
+        };
+        Ok(PostgresInstance {
+            container_name,
+            container_guard,
+            addr: None,
+            load: None,
+        })
+    }
+
+    async fn ready(&self, instance: &mut PostgresInstance) -> anyhow::Result<()> {
+        let psql = psql::Psql::new(&self.config, &instance.container_name, None);
+        psql.wait_ready().await?;
+        if self.config.load_mode.from_host() {
+            let runtime = &self.config.runtime;
+            instance.addr = Some(runtime.host_port(&instance.container_name, 5432).await?);
+        }
+        Ok(())
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut PostgresInstance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let psql = psql::Psql::new(&self.config, &instance.container_name, instance.addr);
+        psql.migrate().await?;
+        let load = match self.config.load_mode {
+            crate::LoadMode::Driver => client::prepare(&psql, input).await?,
+            crate::LoadMode::Exec | crate::LoadMode::Host => {
+                insert_bulk::prepare(&psql, input, V::STRATEGY).await?
+            }
+        };
+        instance.load = Some(load);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: PostgresInstance) -> anyhow::Result<Self::Bencher> {
+        let load = instance.load.ok_or(anyhow::anyhow!(
+            "fixture of {} is not loaded",
+            instance.container_name
+        ))?;
+        Ok(crate::docker::Bench::new(
+            self.config.clone(),
+            load,
+            instance.container_guard,
+        ))
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is stored once with its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let psql = &psql::Psql::new(&self.config, container_name, None);
+        let query = |sql: String| async move { psql.query(&sql, Phase::Verify).await };
+        verify::sql_rows(input, &outcome.loader, '|', query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::psql::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::Runtime;
+    use crate::testing::stdout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("postgres_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = crate::testing::script_sql_rows(&fake, &bench_input, '|')?;
+        fake.script_exec("COPY user_transactions", stdout("COPY 10\n"))
+            .script_exec("pg_total_relation_size", stdout("16384\n"));
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Postgres = Postgres::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(16384));
+
+        // A missing row fails the verification
+        fake.script_exec("count(*)", stdout(format!("9|{}\n", expected.users)));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use super::psql::Psql;
+use crate::docker::Load;
+use crate::driver::{self, PostgresDriver};
+use crate::workload::WorkloadInput;
+
+/// Measured load inserting every row through one [`PostgresDriver`] per client.
+pub(super) async fn prepare(psql: &Psql<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    let url = psql.url()?;
+    let operations = input.client_operations()?;
+    let mut drivers = vec![];
+    for _ in 0..input.clients {
+        drivers.push(PostgresDriver::connect(&url).await?);
+    }
+    Ok(Load::Host(Box::pin(driver::run_concurrent(
+        drivers, operations,
+    ))))
+}
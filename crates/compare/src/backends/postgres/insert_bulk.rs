@@ -1,166 +1,453 @@
-use crate::docker::Docker;
+use db_test_model::DataRow;
+use db_test_model::temp::CsvFilesManager;
+use sqlx::{Connection, Executor};
 
-pub struct PostgresInsertBulk {
-    containers_pool: crate::docker::Pool<Self>,
+use super::psql::Psql;
+use super::{Container, Variant};
+use crate::LoaderReport;
+use crate::docker::{Docker, Load};
+use crate::verify::literal;
+use crate::workload::WorkloadInput;
+
+/// Rows per statement or transaction of the batched strategies.
+pub const BATCH_SIZE_ENV: &str = "DB_TEST_POSTGRES_BATCH_SIZE";
+
+const INSERT_ROW: &str = "insert into user_transactions (user_addr, trans_time, trans_hash) \
+     values ($1, $2, $3)";
+const INSERT_UNNEST: &str = "insert into user_transactions (user_addr, trans_time, trans_hash) \
+     select * from unnest($1::text[], $2::bigint[], $3::text[])";
+
+/// Format of the data read by `COPY FROM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    Csv,
+    /// Tab separated, with backslash escapes
+    Text,
+    /// Postgres binary tuples
+    Binary,
 }
 
-impl crate::docker::Docker for PostgresInsertBulk {
-    const IMAGE_NAME: &'static str = "postgres";
-    const CONTAINER_NAME_PREFIX: &'static str = "bench-postgres-insert-bulk";
-
-    async fn create_container(
-        docker: &bollard::Docker,
-        container_name: &str,
-    ) -> anyhow::Result<bollard::secret::ContainerCreateResponse> {
-        let options = bollard::container::CreateContainerOptions {
-            name: container_name,
-            platform: None,
-        };
-        let config = bollard::container::Config {
-            image: Some(Self::IMAGE_NAME),
-            env: Some(vec!["POSTGRES_HOST_AUTH_METHOD=trust"]),
-            ..Default::default()
-        };
-        let container = docker.create_container(Some(options), config).await?;
-        Ok(container)
+impl CopyFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            CopyFormat::Csv => "csv",
+            CopyFormat::Text => "text",
+            CopyFormat::Binary => "binary",
+        }
     }
 }
 
-impl crate::Backend for PostgresInsertBulk {
-    type Input = crate::InsertBulkBenchInput;
-
-    async fn setup(docker: &bollard::Docker) -> Self {
-        let containers_pool = crate::docker::Pool::new(docker.clone());
-        PostgresInsertBulk { containers_pool }
-    }
+/// How the measured load writes the dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// One `COPY FROM` of the whole dataset
+    Copy(CopyFormat),
+    /// Multi-row `INSERT … VALUES` statements
+    InsertValues,
+    /// Prepared single-row `INSERT`s, one transaction per batch
+    Prepared,
+    /// Prepared `INSERT … SELECT unnest(…)` of one array per column
+    Unnest,
+}
 
-    #[rustfmt::skip]
-    #[allow(refining_impl_trait)]
-    async fn prepare(&self, docker: &bollard::Docker) -> PostgresInsertBulkBench {
-        let container_info = self.containers_pool.create_container()
-            .await
-            .expect("cannot create postgres container");
-        let conatiner_name = container_info.container_name.clone();
-        let container_guard = PostgresInsertBulk::start_container(docker, conatiner_name)
-            .await
-            .expect("cannot start postgres container");
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        PostgresInsertBulkBench::new(container_info, container_guard)
+impl Strategy {
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::Copy(CopyFormat::Csv) => "copy-csv",
+            Strategy::Copy(CopyFormat::Text) => "copy-text",
+            Strategy::Copy(CopyFormat::Binary) => "copy-binary",
+            Strategy::InsertValues => "insert-values",
+            Strategy::Prepared => "prepared",
+            Strategy::Unnest => "unnest",
+        }
     }
 }
 
-pub struct PostgresInsertBulkBench {
-    container_info: crate::docker::ContainerInfo,
-    container_guard: Option<crate::docker::ContainerGuard>,
-    bench_exec_id: Option<crate::docker::ExecId>,
+macro_rules! variant {
+    ($(#[$doc:meta])* $variant:ident, $name:literal, $strategy:expr) => {
+        $(#[$doc])*
+        pub struct $variant;
+
+        impl Variant for $variant {
+            const NAME: &'static str = $name;
+            const CONTAINER_NAME_PREFIX: &'static str = concat!("bench-", $name);
+            const STRATEGY: Strategy = $strategy;
+        }
+    };
 }
 
-impl PostgresInsertBulkBench {
-    const BULK_FILE: &str = "/tmp/items";
+variant!(
+    /// `COPY FROM` the data file itself
+    CopyCsv,
+    "postgres",
+    Strategy::Copy(CopyFormat::Csv)
+);
+variant!(
+    CopyText,
+    "postgres-copy-text",
+    Strategy::Copy(CopyFormat::Text)
+);
+variant!(
+    CopyBinary,
+    "postgres-copy-binary",
+    Strategy::Copy(CopyFormat::Binary)
+);
+variant!(
+    ValuesBatches,
+    "postgres-insert-values",
+    Strategy::InsertValues
+);
+variant!(PreparedBatches, "postgres-prepared", Strategy::Prepared);
+variant!(UnnestBatches, "postgres-unnest", Strategy::Unnest);
 
-    fn new(
-        container_info: crate::docker::ContainerInfo,
-        container_guard: crate::docker::ContainerGuard,
-    ) -> Self {
-        let container_guard = Some(container_guard);
-        PostgresInsertBulkBench {
-            container_info,
-            container_guard,
-            bench_exec_id: None,
-        }
+/// Measured load writing the whole dataset with `strategy`, from the
+/// uploaded file through `psql` or from the host depending on the load mode.
+pub(super) async fn prepare(
+    psql: &Psql<'_>,
+    input: &WorkloadInput,
+    strategy: Strategy,
+) -> anyhow::Result<Load> {
+    let batch_size = crate::workload::batch_size_from_env(BATCH_SIZE_ENV)?;
+    if psql.config().load_mode.from_host() {
+        let rows = db_test_model::read_data_file(&input.file_path)?;
+        let connection = psql.connect().await?;
+        let host_load = insert_from_host(connection, strategy, rows, batch_size);
+        return Ok(Load::Host(Box::pin(host_load)));
     }
 
-    async fn migrate(docker: &bollard::Docker, container_name: &str) {
-        for migration in sqlx::migrate!().iter() {
-            let migration_exec = {
-                let migration_command = format!("psql -c \"{}\" -U postgres", migration.sql);
-                let command = vec!["bash", "-c", migration_command.as_str()];
-                PostgresInsertBulk::create_exec(docker, container_name, command)
-                    .await
-                    .expect("cannot create postgres prepare exec")
-            };
-            let migration_exec_id = crate::docker::ExecId(migration_exec.id.into_boxed_str());
-            let migration_exec_attach = PostgresInsertBulk::start_exec(docker, &migration_exec_id)
-                .await
-                .expect("cannot start postgres migration command");
-            PostgresInsertBulk::attached_exec(migration_exec_attach).await;
+    // Upload data file
+    let config = psql.config();
+    let container_name = psql.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = match strategy {
+        Strategy::Copy(CopyFormat::Csv) => {
+            CsvFilesManager::tar_data_file(&input.file_path, dst_file)?
         }
-    }
+        _ => {
+            let rows = db_test_model::read_data_file(&input.file_path)?;
+            let encoded = encode(strategy, &rows, batch_size)?;
+            let stem = input.file_path.file_stem().unwrap_or_default();
+            let name = format!("{}-{}", stem.display(), strategy.name());
+            CsvFilesManager::tar_encoded(&input.file_path, &name, &encoded, dst_file)?
+        }
+    };
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
 
-    async fn create_bulk_file(docker: &bollard::Docker, items_count: u64, container_name: &str) {
-        let prepare_exec = {
-            let repeat_insert = format!(
-                "yes \'qwerty 1742392583 qwerty\' | head -{} > {}",
-                items_count,
-                Self::BULK_FILE
+    // Prepare load exec
+    let copy;
+    let command = match strategy {
+        Strategy::Copy(format) => {
+            copy = format!(
+                "COPY user_transactions FROM '{data_file}' WITH (FORMAT {})",
+                format.name()
             );
-            let exec_command = vec!["bash", "-c", repeat_insert.as_str()];
-            PostgresInsertBulk::create_exec(docker, container_name, exec_command)
-                .await
-                .expect("cannot create postgres prepare exec")
-        };
-        let prepare_exec_id = crate::docker::ExecId(prepare_exec.id.into_boxed_str());
-        let prepare_exec_attach = PostgresInsertBulk::start_exec(docker, &prepare_exec_id)
-            .await
-            .expect("cannot start redis insert bulk command");
-        PostgresInsertBulk::attached_exec(prepare_exec_attach).await;
+            Psql::command(&["\\timing on", &copy])
+        }
+        Strategy::InsertValues | Strategy::Prepared | Strategy::Unnest => {
+            let mut command = Psql::command(&["\\timing on"]);
+            command.extend(["-f", &data_file]);
+            command
+        }
+    };
+    let exec_id = Container::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
+}
+
+/// Data read by the `COPY`, or the script of statements inserting the rows.
+fn encode(strategy: Strategy, rows: &[DataRow], batch_size: usize) -> anyhow::Result<Vec<u8>> {
+    let mut encoded = vec![];
+    match strategy {
+        Strategy::Copy(CopyFormat::Csv) => {
+            let mut writer = csv::Writer::from_writer(&mut encoded);
+            for row in rows {
+                writer.write_record([&row.user, &row.timestamp, &row.transaction_id])?;
+            }
+            writer.flush()?;
+        }
+        Strategy::Copy(CopyFormat::Text) => {
+            for row in rows {
+                let fields = [&row.user, &row.timestamp, &row.transaction_id];
+                let fields = fields.map(|field| escape_text(field));
+                encoded.extend_from_slice(fields.join("\t").as_bytes());
+                encoded.push(b'\n');
+            }
+        }
+        Strategy::Copy(CopyFormat::Binary) => encode_binary(rows, &mut encoded)?,
+        Strategy::InsertValues => {
+            for batch in rows.chunks(batch_size) {
+                encoded.extend_from_slice(insert_values(batch)?.as_bytes());
+                encoded.push(b'\n');
+            }
+        }
+        Strategy::Prepared => {
+            let prepare = format!("prepare insert_row (text, bigint, text) as {INSERT_ROW};\n");
+            encoded.extend_from_slice(prepare.as_bytes());
+            for batch in rows.chunks(batch_size) {
+                encoded.extend_from_slice(b"begin;\n");
+                for row in batch {
+                    let execute = format!(
+                        "execute insert_row({}, {}, {});\n",
+                        literal(&row.user),
+                        timestamp(row)?,
+                        literal(&row.transaction_id)
+                    );
+                    encoded.extend_from_slice(execute.as_bytes());
+                }
+                encoded.extend_from_slice(b"commit;\n");
+            }
+        }
+        Strategy::Unnest => {
+            let prepare = format!("prepare insert_batch as {INSERT_UNNEST};\n");
+            encoded.extend_from_slice(prepare.as_bytes());
+            for batch in rows.chunks(batch_size) {
+                let (users, timestamps, transaction_ids) = columns(batch)?;
+                let array = |values: Vec<String>| format!("array[{}]", values.join(", "));
+                let execute = format!(
+                    "execute insert_batch({}, {}, {});\n",
+                    array(users.into_iter().map(literal).collect()),
+                    array(timestamps.iter().map(i64::to_string).collect()),
+                    array(transaction_ids.into_iter().map(literal).collect()),
+                );
+                encoded.extend_from_slice(execute.as_bytes());
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+fn timestamp(row: &DataRow) -> anyhow::Result<i64> {
+    row.timestamp
+        .parse()
+        .map_err(|err| anyhow::anyhow!("invalid timestamp of {row:?}: {err}"))
+}
+
+/// One array per column, in the order of the rows.
+fn columns(rows: &[DataRow]) -> anyhow::Result<(Vec<&str>, Vec<i64>, Vec<&str>)> {
+    let mut columns = (vec![], vec![], vec![]);
+    for row in rows {
+        columns.0.push(row.user.as_str());
+        columns.1.push(timestamp(row)?);
+        columns.2.push(row.transaction_id.as_str());
     }
+    Ok(columns)
 }
 
-impl crate::Bench for PostgresInsertBulkBench {
-    type Input = crate::InsertBulkBenchInput;
+fn insert_values(rows: &[DataRow]) -> anyhow::Result<String> {
+    let mut values = vec![];
+    for row in rows {
+        values.push(format!(
+            "({}, {}, {})",
+            literal(&row.user),
+            timestamp(row)?,
+            literal(&row.transaction_id)
+        ));
+    }
+    Ok(format!(
+        "insert into user_transactions (user_addr, trans_time, trans_hash) values {};",
+        values.join(", ")
+    ))
+}
+
+fn escape_text(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
 
-    async fn run(&mut self, input: Self::Input) -> crate::docker::ContainerGuard {
-        let exec_id = self.bench_exec_id.as_ref().unwrap();
-        let attach = PostgresInsertBulk::start_exec(&input.docker, exec_id)
-            .await
-            .expect("cannot start postgres insert bulk command");
-        PostgresInsertBulk::attached_exec(attach).await;
-        self.container_guard.take().unwrap()
+/// Binary `COPY` data: signature, flags and header extension length, tuples
+/// of length-prefixed fields, then the trailer.
+fn encode_binary(rows: &[DataRow], encoded: &mut Vec<u8>) -> anyhow::Result<()> {
+    encoded.extend_from_slice(b"PGCOPY\n\xff\r\n\0");
+    encoded.extend_from_slice(&0i32.to_be_bytes());
+    encoded.extend_from_slice(&0i32.to_be_bytes());
+    for row in rows {
+        encoded.extend_from_slice(&3i16.to_be_bytes());
+        let timestamp = timestamp(row)?.to_be_bytes();
+        for field in [
+            row.user.as_bytes(),
+            &timestamp,
+            row.transaction_id.as_bytes(),
+        ] {
+            encoded.extend_from_slice(&i32::try_from(field.len())?.to_be_bytes());
+            encoded.extend_from_slice(field);
+        }
     }
+    encoded.extend_from_slice(&(-1i16).to_be_bytes());
+    Ok(())
+}
 
-    async fn prepare(&mut self, input: &Self::Input) -> () {
-        let container_name = &self.container_info.container_name;
-        Self::migrate(&input.docker, container_name).await;
-        Self::create_bulk_file(&input.docker, input.items_count, container_name).await;
-        let bench_exec = {
-            let insert_query ="COPY user_transactions FROM '/tmp/items' WITH (FORMAT TEXT);";
-            let insert_command = format!("psql -c \"{insert_query}\" -U postgres");
-            let exec_command = vec!["bash", "-c", insert_command.as_str()];
-            PostgresInsertBulk::create_exec(&input.docker, container_name, exec_command)
-                .await
-                .expect("cannot create postgres bench exec")
-        };
-        self.bench_exec_id = Some(crate::docker::ExecId(bench_exec.id.into_boxed_str()));
+async fn insert_from_host(
+    mut connection: sqlx::PgConnection,
+    strategy: Strategy,
+    rows: Vec<DataRow>,
+    batch_size: usize,
+) -> anyhow::Result<LoaderReport> {
+    let mut rows_loaded = 0;
+    match strategy {
+        Strategy::Copy(format) => {
+            let data = encode(strategy, &rows, batch_size)?;
+            let copy = format!(
+                "COPY user_transactions FROM STDIN WITH (FORMAT {})",
+                format.name()
+            );
+            let mut copy = connection.copy_in_raw(&copy).await?;
+            copy.send(data).await?;
+            rows_loaded = copy.finish().await?;
+        }
+        Strategy::InsertValues => {
+            for batch in rows.chunks(batch_size) {
+                let insert = insert_values(batch)?;
+                // Without arguments, the statement is sent as a simple query
+                let result = connection.execute(insert.as_str()).await?;
+                rows_loaded += result.rows_affected();
+            }
+        }
+        Strategy::Prepared => {
+            for batch in rows.chunks(batch_size) {
+                let mut transaction = connection.begin().await?;
+                for row in batch {
+                    let insert = sqlx::query(INSERT_ROW)
+                        .bind(&row.user)
+                        .bind(timestamp(row)?)
+                        .bind(&row.transaction_id);
+                    rows_loaded += insert.execute(&mut *transaction).await?.rows_affected();
+                }
+                transaction.commit().await?;
+            }
+        }
+        Strategy::Unnest => {
+            for batch in rows.chunks(batch_size) {
+                let (users, timestamps, transaction_ids) = columns(batch)?;
+                let insert = sqlx::query(INSERT_UNNEST)
+                    .bind(users)
+                    .bind(timestamps)
+                    .bind(transaction_ids);
+                rows_loaded += insert.execute(&mut connection).await?.rows_affected();
+            }
+        }
     }
+    Ok(LoaderReport {
+        rows_loaded: Some(rows_loaded),
+        ..Default::default()
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::Postgres;
+    use super::super::psql::testing::ready_fake;
     use super::*;
-    use crate::{Backend, Bench};
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{ExecOutput, Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_migrates_and_uploads_dataset() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        fake.script_exec(
+            "COPY user_transactions",
+            ExecOutput {
+                stdout: "Timing is on.\nCOPY 10\nTime: 1.000 ms\n".into(),
+                ..Default::default()
+            },
+        );
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Postgres = Postgres::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("postgres_prepare_uploads_dataset", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-postgres-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert!(execs[0].starts_with("pg_isready"));
+        assert!(execs[1].contains("create table if not exists user_transactions"));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "psql -v ON_ERROR_STOP=1 -U postgres -c \\timing on \
+                 -c COPY user_transactions FROM '/tmp/items' WITH (FORMAT csv)"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
+
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        assert_eq!(
+            outcome.loader.server_elapsed,
+            Some(std::time::Duration::from_millis(1))
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn batched_variants_run_statements_file() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Postgres<UnnestBatches> = Postgres::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("postgres_batched_variants", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let load = fake.calls().into_iter().rev().find_map(|call| match call {
+            Call::CreateExec {
+                container_name,
+                cmd,
+            } => Some((container_name, cmd.join(" "))),
+            _ => None,
+        });
+        assert_eq!(
+            load,
+            Some((
+                "bench-postgres-unnest-0".into(),
+                "psql -v ON_ERROR_STOP=1 -U postgres -c \\timing on -f /tmp/items".into()
+            ))
+        );
+        Ok(())
+    }
 
     #[test]
-    fn run_container() -> anyhow::Result<()> {
-        let items_count = 100_000;
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let docker = bollard::Docker::connect_with_local_defaults()
-            .expect("cannot connect to docker daemon");
-        let backend = runtime.block_on(PostgresInsertBulk::setup(&docker));
-        let bench_input = crate::InsertBulkBenchInput {
-            docker: docker.clone(),
-            items_count,
+    fn encode_every_strategy() -> anyhow::Result<()> {
+        let row = |user: &str, timestamp: &str| DataRow {
+            user: user.into(),
+            timestamp: timestamp.into(),
+            transaction_id: format!("{user}{timestamp}"),
         };
-        let mut bench = tokio::task::block_in_place(|| {
-            runtime.block_on(async {
-                let mut bench = backend.prepare(&docker).await;
-                bench.prepare(&bench_input).await;
-                bench
-            })
-        });
-        let _enter = runtime.enter();
-        let _guard = runtime.block_on(bench.run(bench_input));
-        std::mem::forget(_guard);
+        let rows = [row("a", "1"), row("b", "2"), row("c", "3")];
+        let encode = |strategy| -> anyhow::Result<String> {
+            Ok(String::from_utf8_lossy(&encode(strategy, &rows, 2)?).into_owned())
+        };
+        assert_eq!(
+            encode(Strategy::Copy(CopyFormat::Csv))?,
+            "a,1,a1\nb,2,b2\nc,3,c3\n"
+        );
+        assert_eq!(
+            encode(Strategy::Copy(CopyFormat::Text))?,
+            "a\t1\ta1\nb\t2\tb2\nc\t3\tc3\n"
+        );
+        assert_eq!(
+            encode(Strategy::InsertValues)?,
+            "insert into user_transactions (user_addr, trans_time, trans_hash) \
+             values ('a', 1, 'a1'), ('b', 2, 'b2');\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) \
+             values ('c', 3, 'c3');\n"
+        );
+        let prepared = encode(Strategy::Prepared)?;
+        assert_eq!(prepared.matches("begin;").count(), 2);
+        assert_eq!(prepared.matches("execute insert_row(").count(), 3);
+        let unnest = encode(Strategy::Unnest)?;
+        assert!(unnest.ends_with("execute insert_batch(array['c'], array[3], array['c3']);\n"));
+
+        let binary = super::encode(Strategy::Copy(CopyFormat::Binary), &rows, 2)?;
+        assert!(binary.starts_with(b"PGCOPY\n\xff\r\n\0"));
+        // Header, 3 tuples of 3 length-prefixed fields, trailer
+        let tuple = 2 + (4 + 1) + (4 + 8) + (4 + 2);
+        assert_eq!(binary.len(), 11 + 4 + 4 + 3 * tuple + 2);
         Ok(())
     }
 }
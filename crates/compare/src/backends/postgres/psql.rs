@@ -0,0 +1,196 @@
+use sqlx::Connection;
+
+use super::Container;
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// Schema every load starts from.
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+
+/// Runs SQL through `psql` inside a started container.
+pub(super) struct Psql<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+    /// Published client port, connected to from the host
+    addr: Option<std::net::SocketAddr>,
+}
+
+impl<'a> Psql<'a> {
+    const USER: &'static str = "postgres";
+    /// Printed by `pg_isready` once the server accepts clients.
+    const ACCEPTING: &'static str = "accepting connections";
+    const READY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    pub(super) fn new(
+        config: &'a crate::BackendConfig,
+        container_name: &'a str,
+        addr: Option<std::net::SocketAddr>,
+    ) -> Self {
+        Psql {
+            config,
+            container_name,
+            addr,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// Connection url of the published client port, in host and driver load modes only.
+    pub(super) fn url(&self) -> anyhow::Result<String> {
+        let addr = self
+            .addr
+            .ok_or(anyhow::anyhow!("client port is not published"))?;
+        Ok(format!("postgres://{}@{addr}/postgres", Self::USER))
+    }
+
+    /// Connects from the host, see [`Psql::url`].
+    pub(super) async fn connect(&self) -> anyhow::Result<sqlx::PgConnection> {
+        Ok(sqlx::PgConnection::connect(&self.url()?).await?)
+    }
+
+    /// `psql` running `commands` in order, stopping at the first error.
+    pub(super) fn command<'c>(commands: &[&'c str]) -> Vec<&'c str> {
+        let mut cmd = vec!["psql", "-v", "ON_ERROR_STOP=1", "-U", Self::USER];
+        for command in commands {
+            cmd.extend(["-c", command]);
+        }
+        cmd
+    }
+
+    /// Runs `sql` as part of `phase`, returning its unaligned rows.
+    pub(super) async fn query(&self, sql: &str, phase: Phase) -> anyhow::Result<String> {
+        let mut cmd = Self::command(&[sql]);
+        cmd.push("-tA");
+        let output = Container::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !output.stderr.contains("ERROR:"),
+            "psql failed: {}",
+            output.stderr.trim()
+        );
+        Ok(())
+    }
+
+    /// Waits until the server accepts TCP clients.
+    ///
+    /// The image initializes the database with a temporary server listening
+    /// on the unix socket only, so the socket alone does not tell it is up.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let cmd = vec!["pg_isready", "-h", "localhost", "-U", Self::USER];
+            let output = Container::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.contains(Self::ACCEPTING) {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Creates the schema from the embedded migrations.
+    pub(super) async fn migrate(&self) -> anyhow::Result<()> {
+        for migration in MIGRATOR.iter() {
+            self.query(&migration.sql, Phase::LoadFixture)
+                .await
+                .map_err(|err| {
+                    anyhow::anyhow!("migration {} failed: {err}", migration.description)
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Rows of every `COPY n` and `INSERT 0 n` tag, and the server time of
+    /// every statement printed with `\timing on`.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        Self::ensure_no_error(output)?;
+        let mut rows_loaded = None;
+        let mut server_elapsed = None;
+        for line in output.stdout.lines().map(str::trim) {
+            let rows = line
+                .strip_prefix("COPY ")
+                .or_else(|| line.strip_prefix("INSERT 0 "));
+            if let Some(rows) = rows {
+                *rows_loaded.get_or_insert(0) += rows.parse::<u64>()?;
+            }
+            let millis = line
+                .strip_prefix("Time: ")
+                .and_then(|time| time.split_once(" ms"));
+            if let Some((millis, _)) = millis {
+                let elapsed = std::time::Duration::from_secs_f64(millis.parse::<f64>()? / 1000.0);
+                *server_elapsed.get_or_insert(std::time::Duration::ZERO) += elapsed;
+            }
+        }
+        let rows_loaded = rows_loaded.ok_or(anyhow::anyhow!(
+            "no row count in psql output: {}",
+            output.stderr
+        ))?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded),
+            errors: 0,
+            server_elapsed,
+            latency: None,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use crate::runtime::FakeRuntime;
+
+    /// Fake runtime whose postgres servers are ready right away.
+    pub(in crate::backends::postgres) fn ready_fake() -> FakeRuntime {
+        crate::testing::ready_fake("pg_isready", "localhost:5432 - accepting connections\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let output = ExecOutput {
+            stdout: "Timing is on.\nCOPY 500\nTime: 1234.500 ms (00:01.235)\n".into(),
+            ..Default::default()
+        };
+        let loader = Psql::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(500));
+        assert_eq!(
+            loader.server_elapsed,
+            Some(std::time::Duration::from_micros(1_234_500))
+        );
+
+        let output = ExecOutput {
+            stdout: "PREPARE\nTime: 0.5 ms\nINSERT 0 2\nTime: 1 ms\nINSERT 0 1\nTime: 1 ms\n"
+                .into(),
+            ..Default::default()
+        };
+        let loader = Psql::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(3));
+        assert_eq!(
+            loader.server_elapsed,
+            Some(std::time::Duration::from_micros(2_500))
+        );
+
+        let output = ExecOutput {
+            stdout: "Timing is on.\n".into(),
+            stderr: "ERROR:  relation \"user_transactions\" does not exist\n".into(),
+            ..Default::default()
+        };
+        assert!(Psql::parse_load_output(&output).is_err());
+        Ok(())
+    }
+}
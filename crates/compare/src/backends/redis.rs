@@ -1,8 +1,411 @@
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::{self, Footprint};
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod client;
 pub mod insert_bulk;
+mod pipe;
+pub mod queries;
+
+/// Redis keeping the transactions of every user in a sorted set, scored by
+/// their timestamps, on the engine and with the persistence of `V`.
+pub struct Redis<V: Variant = Defaults> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+/// Persistence mode or engine benchmarked as a backend of its own.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    const ENGINE: Engine;
+    /// Arguments of [`Engine::server`], the image defaults apply when empty
+    const SERVER_ARGS: &[&str];
+}
+
+/// Redis compatible server image, with the binaries it ships.
+///
+/// Every engine takes the same RESP dataset, piped into its `redis-cli`.
+pub struct Engine {
+    pub image_name: &'static str,
+    pub server: &'static str,
+    pub cli: &'static str,
+    /// `INFO memory` field of the memory held by the dataset
+    pub memory_field: &'static str,
+    pub artifacts: &'static [Artifact],
+}
+
+macro_rules! engine {
+    ($image_name:literal, $server:literal, $cli:literal, $memory_field:literal) => {
+        Engine {
+            image_name: $image_name,
+            server: $server,
+            cli: $cli,
+            memory_field: $memory_field,
+            artifacts: &[
+                Artifact::Logs,
+                Artifact::Command {
+                    name: "config",
+                    script: concat!($cli, " CONFIG GET '*'"),
+                },
+                Artifact::Command {
+                    name: "info",
+                    script: concat!($cli, " INFO"),
+                },
+                // Working directory is the data directory
+                Artifact::Command {
+                    name: "data-dir",
+                    script: "du -ab .",
+                },
+            ],
+        }
+    };
+}
+
+impl Engine {
+    pub const REDIS: Engine = engine!("redis", "redis-server", "redis-cli", "used_memory_dataset");
+    pub const VALKEY: Engine = engine!(
+        "valkey/valkey",
+        "valkey-server",
+        "valkey-cli",
+        "used_memory_dataset"
+    );
+    pub const KEYDB: Engine = engine!(
+        "eqalpha/keydb",
+        "keydb-server",
+        "keydb-cli",
+        "used_memory_dataset"
+    );
+    /// Ships `redis-cli` and reports its memory without a dataset breakdown.
+    pub const DRAGONFLY: Engine = engine!(
+        "docker.dragonflydb.io/dragonflydb/dragonfly",
+        "dragonfly",
+        "redis-cli",
+        "used_memory"
+    );
+}
+
+macro_rules! variant {
+    ($(#[$doc:meta])* $variant:ident, $name:literal, $engine:expr, [$($arg:literal),*]) => {
+        $(#[$doc])*
+        pub struct $variant;
+
+        impl Variant for $variant {
+            const NAME: &'static str = $name;
+            const CONTAINER_NAME_PREFIX: &'static str = concat!("bench-", $name);
+            const ENGINE: Engine = $engine;
+            const SERVER_ARGS: &'static [&'static str] = &[$($arg),*];
+        }
+    };
+}
+
+variant!(
+    /// Persistence of the image, RDB snapshots on its default schedule
+    Defaults,
+    "redis",
+    Engine::REDIS,
+    []
+);
+variant!(
+    NoPersistence,
+    "redis-no-persistence",
+    Engine::REDIS,
+    ["--save", "", "--appendonly", "no"]
+);
+variant!(
+    /// Snapshot once a thousand keys changed within a minute
+    RdbSnapshots,
+    "redis-rdb",
+    Engine::REDIS,
+    ["--save", "60 1000", "--appendonly", "no"]
+);
+variant!(
+    AofEverySec,
+    "redis-aof-everysec",
+    Engine::REDIS,
+    [
+        "--save",
+        "",
+        "--appendonly",
+        "yes",
+        "--appendfsync",
+        "everysec"
+    ]
+);
+variant!(
+    AofAlways,
+    "redis-aof-always",
+    Engine::REDIS,
+    [
+        "--save",
+        "",
+        "--appendonly",
+        "yes",
+        "--appendfsync",
+        "always"
+    ]
+);
+variant!(Valkey, "valkey", Engine::VALKEY, []);
+variant!(
+    /// Multi-threaded KeyDB
+    KeyDb,
+    "keydb",
+    Engine::KEYDB,
+    ["--server-threads", "4"]
+);
+variant!(
+    /// Dragonfly, letting the verification script read the keys it lists
+    Dragonfly,
+    "dragonfly",
+    Engine::DRAGONFLY,
+    ["--logtostderr", "--default_lua_flags=allow-undeclared-keys"]
+);
+
+/// Started container, with the measured load once the fixture is loaded.
+pub struct RedisInstance {
+    container_name: Box<str>,
+    container_guard: crate::docker::ContainerGuard,
+    /// Published client port, for loads connecting from the host
+    addr: Option<std::net::SocketAddr>,
+    load: Option<crate::docker::Load>,
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use Redis as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = V::ENGINE.image_name;
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some("/data");
+    const CLIENT_PORT: Option<u16> = Some(6379);
+    const ARTIFACTS: &'static [Artifact] = V::ENGINE.artifacts;
+
+    fn container_spec() -> ContainerSpec {
+        let spec = ContainerSpec::new(Self::IMAGE_NAME);
+        match V::SERVER_ARGS {
+            [] => spec,
+            args => spec.cmd(std::iter::once(V::ENGINE.server).chain(args.iter().copied())),
+        }
+    }
+
+    /// `redis-cli --pipe` ends with an `errors: N, replies: M` summary line.
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        let summary = output
+            .stdout
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("errors: "))
+            .ok_or(anyhow::anyhow!(
+                "no summary in redis-cli output: {}",
+                output.stderr
+            ))?;
+        let (errors, replies) = summary
+            .split_once(", replies: ")
+            .ok_or(anyhow::anyhow!("invalid redis-cli summary: {summary}"))?;
+        let errors: u64 = errors.parse()?;
+        let replies: u64 = replies.parse()?;
+        let rows_loaded = replies
+            .checked_sub(errors)
+            .ok_or(anyhow::anyhow!("invalid redis-cli summary: {summary}"))?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded),
+            errors,
+            server_elapsed: None,
+            latency: None,
+        })
+    }
+
+    /// Memory held by the dataset itself, leaving out the server overhead.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let cmd = vec![V::ENGINE.cli, "INFO", "memory"];
+        let info = Container::run_cmd(config, container_name, cmd, Phase::Footprint).await?;
+        let memory_bytes = footprint::parse_info_field(&info.stdout, V::ENGINE.memory_field)?;
+        let disk_bytes = footprint::data_dir_size(config, container_name, "/data").await?;
+        Ok(Footprint {
+            memory_bytes: Some(memory_bytes),
+            disk_bytes: Some(disk_bytes),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = Workload::ALL;
+
+    type Instance = RedisInstance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<RedisInstance> {
+        anyhow::ensure!(
+            input.clients == 1 || self.config.load_mode == crate::LoadMode::Driver,
+            "concurrent clients need the driver load mode"
+        );
+        let container_info = self.containers_pool.create_container().await?;
+        let crate::docker::ContainerInfo { container_name, .. } = container_info;
+        let container_guard = {
+            let container_name = container_name.clone();
+            Self::start_container(&self.config, container_name).await?
+        };
+        Ok(RedisInstance {
+            container_name,
+            container_guard,
+            addr: None,
+            load: None,
+        })
+    }
+
+    /// Waits for the server inside the container, then for its published
+    /// port when the load connects from the host.
+    async fn ready(&self, instance: &mut RedisInstance) -> anyhow::Result<()> {
+        let pipe = pipe::Pipe::new(&self.config, &instance.container_name, V::ENGINE.cli, None);
+        pipe.wait_ready().await?;
+        if self.config.load_mode.from_host() {
+            let runtime = &self.config.runtime;
+            let addr = runtime.host_port(&instance.container_name, 6379).await?;
+            pipe::HostPipe::wait_ready(addr).await?;
+            instance.addr = Some(addr);
+        }
+        Ok(())
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut RedisInstance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let pipe = pipe::Pipe::new(
+            &self.config,
+            &instance.container_name,
+            V::ENGINE.cli,
+            instance.addr,
+        );
+        let load = match input.workload {
+            _ if self.config.load_mode == crate::LoadMode::Driver => {
+                client::prepare(&pipe, input).await?
+            }
+            Workload::BulkInsert => insert_bulk::prepare(&pipe, input).await?,
+            Workload::PointRead | Workload::RangeScan | Workload::Aggregation | Workload::Mixed => {
+                queries::prepare(&pipe, input).await?
+            }
+        };
+        instance.load = Some(load);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: RedisInstance) -> anyhow::Result<Self::Bencher> {
+        let load = instance.load.ok_or(anyhow::anyhow!(
+            "fixture of {} is not loaded",
+            instance.container_name
+        ))?;
+        Ok(crate::docker::Bench::new(
+            self.config.clone(),
+            load,
+            instance.container_guard,
+        ))
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is a member of the sorted set of its user, scored by its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        verify::ensure_loader(&outcome.loader, input.operations_count()?)?;
+        let expected = db_test_model::sample_data_file(&input.file_path, verify::SAMPLE_SIZE)?;
+
+        let config = &self.config;
+        let container_name = outcome.container_guard.container_name();
+        let cmd = vec![V::ENGINE.cli, "EVAL", pipe::Commander::COUNT_MEMBERS, "0"];
+        let members = Container::run_cmd(config, container_name, cmd, Phase::Verify).await?;
+        let members = members.stdout.trim().parse()?;
+        verify::ensure_count("members", input.expected_rows()?, members)?;
+        let cmd = vec![V::ENGINE.cli, "DBSIZE"];
+        let keys = Container::run_cmd(config, container_name, cmd, Phase::Verify).await?;
+        verify::ensure_count("keys", expected.users, keys.stdout.trim().parse()?)?;
+
+        for row in &expected.sample {
+            let cmd = vec![V::ENGINE.cli, "ZSCORE", &row.user, &row.transaction_id];
+            let score = Container::run_cmd(config, container_name, cmd, Phase::Verify).await?;
+            anyhow::ensure!(
+                score.stdout.trim() == row.timestamp,
+                "verification failed: {row:?} is scored {:?}",
+                score.stdout.trim()
+            );
+        }
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_pass_persistence_args() {
+        let cmd = |spec: ContainerSpec| spec.cmd.iter().map(|arg| arg.to_string()).collect();
+        let default: Vec<String> = cmd(Redis::<Defaults>::container_spec());
+        assert!(default.is_empty());
+        assert_eq!(
+            cmd(Redis::<AofAlways>::container_spec()),
+            [
+                "redis-server",
+                "--save",
+                "",
+                "--appendonly",
+                "yes",
+                "--appendfsync",
+                "always"
+            ]
+        );
+        assert_eq!(
+            Redis::<NoPersistence>::CONTAINER_NAME_PREFIX,
+            "bench-redis-no-persistence"
+        );
+        assert_eq!(cmd(Redis::<KeyDb>::container_spec())[0], "keydb-server");
+        assert_eq!(Redis::<Valkey>::IMAGE_NAME, "valkey/valkey");
+        assert!(matches!(
+            Redis::<Valkey>::ARTIFACTS[1],
+            Artifact::Command {
+                script: "valkey-cli CONFIG GET '*'",
+                ..
+            }
+        ));
+    }
+
     #[tokio::test]
+    #[ignore = "requires a running Docker daemon"]
     async fn run_command_inside_container() -> anyhow::Result<()> {
         use futures_util::stream::StreamExt;
 
@@ -30,7 +433,7 @@ mod tests {
                 ..Default::default()
             };
             docker
-                .start_container::<String>(&container_id, Some(options))
+                .start_container::<String>(container_id, Some(options))
                 .await?;
             Ok(())
         }
@@ -62,7 +465,7 @@ mod tests {
         }
 
         let docker = bollard::Docker::connect_with_local_defaults().unwrap();
-        let container_name = format!("example");
+        let container_name = "example".to_string();
 
         let container = create_container(&docker, &container_name).await?;
         start_container(&docker, &container.id).await?;
@@ -0,0 +1,44 @@
+use super::pipe::Pipe;
+use crate::docker::Load;
+use crate::driver::{self, RedisDriver};
+use crate::workload::{Workload, WorkloadInput};
+
+/// Measured load issuing every operation through one [`RedisDriver`] per
+/// client, the dataset of the query workloads is piped in first.
+pub(super) async fn prepare(pipe: &Pipe<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    if input.workload != Workload::BulkInsert {
+        pipe.load_fixture(&input.file_path).await?;
+    }
+    let addr = pipe
+        .addr()
+        .ok_or(anyhow::anyhow!("client port is not published"))?;
+    let operations = input.client_operations()?;
+    let mut drivers = vec![];
+    for _ in 0..input.clients {
+        drivers.push(RedisDriver::connect(addr).await?);
+    }
+    Ok(Load::Host(Box::pin(driver::run_concurrent(
+        drivers, operations,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::pipe::testing::mock_redis;
+    use super::*;
+
+    #[tokio::test]
+    async fn driver_runs_every_workload() -> anyhow::Result<()> {
+        let addr = mock_redis().await?;
+        let file_path = crate::testing::gen_test_csv("redis_driver_runs_every_workload", 10)?;
+        for workload in Workload::ALL {
+            let operations = WorkloadInput::new(*workload, &file_path).client_operations()?;
+            let count = operations.len() as u64;
+            let loader = driver::run(RedisDriver::connect(addr).await?, operations).await?;
+            assert_eq!(loader.rows_loaded, Some(count), "{workload}");
+            assert_eq!(loader.errors, 0, "{workload}");
+            assert!(loader.latency.is_some());
+        }
+        Ok(())
+    }
+}
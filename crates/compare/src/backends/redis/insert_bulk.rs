@@ -1,150 +1,159 @@
-use db_test_model::temp::RespFilesManager;
-
-use crate::docker::Docker;
-
-pub struct RedisInsertBulk {
-    docker: bollard::Docker,
-    containers_pool: crate::docker::Pool<Self>,
+use super::pipe::{Commander, Commands, Pipe};
+use crate::docker::Load;
+use crate::workload::WorkloadInput;
+
+/// Measured load piping the whole dataset, one `ZADD` per row.
+pub(super) async fn prepare(pipe: &Pipe<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    let commands = Commands::DataFile(&input.file_path);
+    pipe.load(commands, Commander::DATA_FILE).await
 }
 
-// Note: this reimport is private and exists only for consistent naming
-use RedisInsertBulk as Backend;
-
-impl crate::docker::Docker for Backend {
-    const IMAGE_NAME: &'static str = "redis";
-    const CONTAINER_NAME_PREFIX: &'static str = "bench-redis-insert-bulk";
-}
-
-struct Commander;
-
-impl Commander {
-    const BULK_FILE_DIR: &str = "/tmp";
-    const BULK_FILE: &str = const_format::formatc!("{}/items", Commander::BULK_FILE_DIR);
-
-    fn redis_insert_piped() -> Vec<&'static str> {
-        const PIPED_INSERT: &str = // "redis-cli PING";
-            const_format::formatc!("cat {} | redis-cli --pipe", Commander::BULK_FILE);
-
-        vec!["bash", "-c", PIPED_INSERT]
+#[cfg(test)]
+mod tests {
+    use super::super::pipe::testing::{pipe_output, ready_fake};
+    use super::super::{Container, Redis};
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::docker::Docker as _;
+    use crate::footprint::Footprint;
+    use crate::runtime::{Runtime, fake::Call};
+    use crate::testing::stdout;
+    use crate::workload::{Workload, WorkloadInput};
+
+    fn bulk_input(test_name: &str) -> anyhow::Result<WorkloadInput> {
+        let file_path = crate::testing::gen_test_csv(test_name, 10)?;
+        Ok(WorkloadInput::new(Workload::BulkInsert, file_path))
     }
-}
-
-impl crate::Backend for Backend {
-    type Input = crate::InsertBulkInput;
-    type Bencher = crate::docker::Bench<Self, crate::InsertBulkInput>;
 
-    async fn setup(docker: bollard::Docker) -> Self {
-        let containers_pool = crate::docker::Pool::new(docker.clone());
-        Backend {
-            docker,
-            containers_pool,
-        }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_uploads_bulk_file() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let backend: Redis = Redis::setup(BackendConfig::new(Runtime::new(fake.clone()))).await;
+        let bench_input = bulk_input("redis_prepare_uploads_bulk_file")?;
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let container_name: Box<str> = "bench-redis-0".into();
+        assert_eq!(
+            fake.calls(),
+            vec![
+                Call::CreateContainer {
+                    container_name: container_name.clone(),
+                    image: "redis".into(),
+                },
+                Call::StartContainer {
+                    container_name: container_name.clone(),
+                },
+                Call::CreateExec {
+                    container_name: container_name.clone(),
+                    cmd: vec!["redis-cli".into(), "PING".into()],
+                },
+                Call::StartExec {
+                    container_name: container_name.clone(),
+                    cmd: vec!["redis-cli".into(), "PING".into()],
+                },
+                Call::UploadArchive {
+                    container_name: container_name.clone(),
+                    dest_path: "/tmp".into(),
+                    entries: vec!["items".into()],
+                },
+                Call::CreateExec {
+                    container_name,
+                    cmd: vec![
+                        "bash".into(),
+                        "-c".into(),
+                        "cat /tmp/items | redis-cli --pipe".into(),
+                    ],
+                },
+            ]
+        );
+        Ok(())
     }
 
-    #[allow(refining_impl_trait)]
-    async fn prepare(&self, input: &Self::Input) -> anyhow::Result<Self::Bencher> {
-        // Create container
-        let container_info = self.containers_pool.create_container().await?;
-        let crate::docker::ContainerInfo { container_name, .. } = container_info;
-
-        let container_guard = {
-            let container_name = container_name.clone();
-            Backend::start_container(&self.docker, container_name).await?
-        };
-
-        // Upload bulk file
-        let dst_file = std::path::Path::new(Commander::BULK_FILE);
-        let dst_path = std::path::PathBuf::from(Commander::BULK_FILE_DIR);
-        let tar_path = RespFilesManager::tar_data_file(&input.file_path, dst_file)?;
-        Backend::upload_large_file(&self.docker, &container_name, tar_path, dst_path).await?;
-
-        // Prepare bench exec
-        let command = Commander::redis_insert_piped();
-        let exec_id = Backend::create_exec(&self.docker, &container_name, command).await?;
-
-        Ok(crate::docker::Bench::new(
-            self.docker.clone(),
-            exec_id,
-            container_guard,
-        ))
+    #[test]
+    fn parse_pipe_summary() -> anyhow::Result<()> {
+        let loader = Container::parse_load_output(&pipe_output(2, 100))?;
+        assert_eq!(loader.rows_loaded, Some(98));
+        assert_eq!(loader.errors, 2);
+        assert!(Container::parse_load_output(&Default::default()).is_err());
+        // Truncated output reporting more errors than replies
+        assert!(Container::parse_load_output(&pipe_output(10, 1)).is_err());
+        Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    // use super::*;
-    // use crate::{Backend, Bencher, Context};
 
-    // #[test]
-    // fn run_bench() -> anyhow::Result<()> {
-    //     let Context {
-    //         runtime, backend, ..
-    //     } = Context::<RedisInsertBulk>::new()?;
-    //     let csv_file_path = gen_test_csv("test_redis_run_bench")?;
-    //     let bench_input = crate::InsertBulkInput {
-    //         file_path: csv_file_path,
-    //     };
-    //     let prepare_bench = async {
-    //         let bench = backend.prepare(&bench_input).await?;
-    //         Ok::<_, anyhow::Error>(bench)
-    //     };
-    //     let bench = runtime.block_on(prepare_bench)?;
-    //     let _enter = runtime.enter();
-    //     let _guard = runtime.block_on(bench.run());
-    //     std::fs::remove_file(bench_input.file_path)?;
-    //     std::mem::forget(_guard);
-    //     Ok(())
-    // }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn run_bench_holds_container() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        fake.script_exec("redis-cli --pipe", pipe_output(0, 10));
+        let backend: Redis = Redis::setup(BackendConfig::new(Runtime::new(fake.clone()))).await;
+        let bench_input = bulk_input("redis_run_bench_holds_container")?;
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let outcome = prepared.bench.await?;
+        assert!(matches!(fake.calls().last(), Some(Call::StartExec { .. })));
+        assert_eq!(fake.containers(), vec!["bench-redis-0".into()]);
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        drop(outcome);
+        assert!(fake.containers().is_empty());
+        Ok(())
+    }
 
-    // #[tokio::test]
-    // async fn file_upload() -> anyhow::Result<()> {
-    //     use RedisInsertBulk as Backend;
-    //     let container_name = "test-redis-insert-bulk-file-upload".to_owned();
-    //     let docker = bollard::Docker::connect_with_local_defaults()?;
-    //     Backend::create_container(&docker, container_name.as_str()).await?;
-    //     let container_guard = {
-    //         let container_name = container_name.clone().into_boxed_str();
-    //         Backend::start_container(&docker, container_name).await?
-    //     };
-    //     let csv_file_path = gen_test_csv("test_redis_file_upload")?;
-    //     let tar_file_path =
-    //         RespFilesManager::tar_data_file(&csv_file_path, &Commander::bulk_file_dest_folder())?;
-    //     let dest_path = Commander::bulk_file_dest_folder();
-    //     Backend::upload_large_file(&docker, &container_name, tar_file_path, dest_path).await?;
-    //     std::mem::forget(container_guard);
-    //     Ok(())
-    // }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn footprint_reads_dataset_memory() -> anyhow::Result<()> {
+        use crate::runtime::ExecOutput;
+
+        let fake = ready_fake();
+        fake.script_exec("redis-cli --pipe", pipe_output(0, 10))
+            .script_exec(
+                "INFO memory",
+                ExecOutput {
+                    stdout: "# Memory\r\nused_memory:900000\r\nused_memory_dataset:1000\r\n".into(),
+                    ..Default::default()
+                },
+            )
+            .script_exec(
+                "du -sb -L /data",
+                ExecOutput {
+                    stdout: "88\t/data\n".into(),
+                    ..Default::default()
+                },
+            );
+        let backend: Redis = Redis::setup(BackendConfig::new(Runtime::new(fake))).await;
+        let bench_input = bulk_input("redis_footprint_reads_dataset_memory")?;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(
+            footprint,
+            Footprint {
+                memory_bytes: Some(1000),
+                disk_bytes: Some(88),
+            }
+        );
+        Ok(())
+    }
 
-    // fn gen_test_csv(csv_file_name: &str) -> anyhow::Result<std::path::PathBuf> {
-    //     let csv_file_name = format!("{csv_file_name}.csv");
-    //     let csv_file_dir = std::env::temp_dir().join("db-test");
-    //     let _ = std::fs::create_dir_all(&csv_file_dir);
-    //     let csv_file_path = csv_file_dir.join(csv_file_name);
-    //     let transactions = [
-    //         (
-    //             "1PuJjnF476W3zXfVYmJfGnouzFDAXakkL4",
-    //             1742574716,
-    //             "577d184106faaafec8a95c3ac9287b914423944667bdf3178a98ddd3af34aaed",
-    //         ),
-    //         (
-    //             "17oDqnkSHeU7Gg4vsXiRVcvCci2YKa7hLg",
-    //             1742574716,
-    //             "9cad2f3a37de9ce804dfdf6249a2dbce90b429eec74f7a6172c6f9b125e95a64",
-    //         ),
-    //         (
-    //             "17oDqnkSHeU7Gg4vsXiRVcvCci2YKa7hLg",
-    //             1742574716,
-    //             "9cad2f3a37de9ce804dfdf6249a2dbce90b429eec74f7a6172c6f9b125e95a64",
-    //         ),
-    //     ];
-    //     let mut csv_writer = csv::Writer::from_path(&csv_file_path)?;
-    //     for transaction in transactions {
-    //         csv_writer.write_record(&[
-    //             transaction.0,
-    //             transaction.1.to_string().as_str(),
-    //             transaction.2,
-    //         ])?;
-    //     }
-    //     Ok(csv_file_path)
-    // }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_loaded_members() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        let bench_input = bulk_input("redis_verify_compares_loaded_members")?;
+        let expected = db_test_model::sample_data_file(&bench_input.file_path, 10)?;
+        fake.script_exec("redis-cli --pipe", pipe_output(0, 10))
+            .script_exec("EVAL", stdout("10\n"))
+            .script_exec("DBSIZE", stdout(format!("{}\n", expected.users)));
+        for row in &expected.sample {
+            let zscore = format!("ZSCORE {} {}", row.user, row.transaction_id);
+            fake.script_exec(&zscore, stdout(format!("{}\n", row.timestamp)));
+        }
+        let backend: Redis = Redis::setup(BackendConfig::new(Runtime::new(fake.clone()))).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+
+        // A silently dropped member fails the verification
+        fake.script_exec("EVAL", stdout("9\n"));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
 }
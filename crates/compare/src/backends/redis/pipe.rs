@@ -0,0 +1,351 @@
+use db_test_model::temp::RespFilesManager;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+use super::Container;
+use crate::LoaderReport;
+use crate::docker::{Docker, Load};
+use crate::timeout::Phase;
+
+pub(super) struct Commander;
+
+impl Commander {
+    const PIPE_FILE_DIR: &str = "/tmp";
+    /// Archived name of the dataset, and of the fixture of the query workloads
+    pub(super) const DATA_FILE: &str = "items";
+    /// Archived name of the measured queries
+    pub(super) const QUERIES_FILE: &str = "queries";
+
+    /// Lua script summing the members of every sorted set.
+    pub(super) const COUNT_MEMBERS: &str = "local n = 0 \
+        for _, key in ipairs(redis.call('KEYS', '*')) do n = n + redis.call('ZCARD', key) end \
+        return n";
+
+    fn redis_insert_piped(cli: &str, pipe_file: &std::path::Path) -> String {
+        format!("cat {} | {cli} --pipe", pipe_file.display())
+    }
+}
+
+/// Commands piped into the server.
+pub(super) enum Commands<'a> {
+    /// `ZADD` of every row of a data file
+    DataFile(&'a std::path::Path),
+    /// Already encoded commands, cached next to `csv_file_path` as `name`
+    Encoded {
+        csv_file_path: &'a std::path::Path,
+        name: String,
+        commands: Vec<u8>,
+        count: u64,
+    },
+}
+
+impl Commands<'_> {
+    fn encode(self) -> anyhow::Result<(Vec<u8>, u64)> {
+        match self {
+            Commands::DataFile(csv_file_path) => RespFilesManager::encode_data_file(csv_file_path),
+            Commands::Encoded {
+                commands, count, ..
+            } => Ok((commands, count)),
+        }
+    }
+
+    fn tar(&self, dst_file: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+        match self {
+            Commands::DataFile(csv_file_path) => {
+                RespFilesManager::tar_data_file(csv_file_path, dst_file)
+            }
+            Commands::Encoded {
+                csv_file_path,
+                name,
+                commands,
+                ..
+            } => RespFilesManager::tar_commands(csv_file_path, name, commands, dst_file),
+        }
+    }
+}
+
+/// Pipes commands into a started container, through `redis-cli --pipe`
+/// inside of it or through [`HostPipe`] depending on the load mode.
+pub(super) struct Pipe<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+    /// `redis-cli` of the engine
+    cli: &'static str,
+    /// Published client port, connected to from the host
+    addr: Option<std::net::SocketAddr>,
+}
+
+impl<'a> Pipe<'a> {
+    const READY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    /// Pipe into `container_name`, through `addr` when the load connects from the host.
+    pub(super) fn new(
+        config: &'a crate::BackendConfig,
+        container_name: &'a str,
+        cli: &'static str,
+        addr: Option<std::net::SocketAddr>,
+    ) -> Self {
+        Pipe {
+            config,
+            container_name,
+            cli,
+            addr,
+        }
+    }
+
+    /// Published client port, in host and driver load modes only.
+    pub(super) fn addr(&self) -> Option<std::net::SocketAddr> {
+        self.addr
+    }
+
+    /// Waits until the server answers `PING` inside the container, `--pipe`
+    /// fails right away with "Connection refused" while it starts.
+    ///
+    /// Retries forever, the lifecycle bounds it with the [`Phase::Ready`] timeout.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let cmd = vec![self.cli, "PING"];
+            let output = Container::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.trim() == "PONG" {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Prepares a load piping `commands`, uploaded as `file_name` in exec mode.
+    pub(super) async fn load(
+        &self,
+        commands: Commands<'_>,
+        file_name: &str,
+    ) -> anyhow::Result<Load> {
+        if let Some(addr) = self.addr {
+            let (commands, commands_count) = commands.encode()?;
+            let host_load = HostPipe::pipe(addr, commands, commands_count);
+            return Ok(Load::Host(Box::pin(host_load)));
+        }
+
+        // Upload pipe file
+        let runtime = &self.config.runtime;
+        let dst_path = std::path::PathBuf::from(Commander::PIPE_FILE_DIR);
+        let dst_file = dst_path.join(file_name);
+        let tar_path = commands.tar(&dst_file)?;
+        let upload = Container::upload_large_file(runtime, self.container_name, tar_path, dst_path);
+        self.config
+            .timeouts
+            .within(Phase::LoadFixture, upload)
+            .await?;
+
+        // Prepare pipe exec
+        let pipe_file = runtime.container_path(self.container_name, &dst_file);
+        let command = Commander::redis_insert_piped(self.cli, &pipe_file);
+        let command = vec!["bash", "-c", command.as_str()];
+        let exec_id = Container::create_exec(runtime, self.container_name, command).await?;
+        Ok(Load::Exec(exec_id))
+    }
+
+    /// Pipes the dataset in before the measurement, failing on any error reply.
+    pub(super) async fn load_fixture(&self, csv_file_path: &std::path::Path) -> anyhow::Result<()> {
+        let load = self.load(Commands::DataFile(csv_file_path), Commander::DATA_FILE);
+        let loader = match load.await? {
+            Load::Exec(exec_id) => {
+                let output =
+                    Container::start_exec(self.config, &exec_id, Phase::LoadFixture).await?;
+                Container::parse_load_output(&output)?
+            }
+            Load::Host(host_load) => host_load.await?,
+        };
+        anyhow::ensure!(
+            loader.errors == 0,
+            "loading the fixture failed with {} errors",
+            loader.errors
+        );
+        Ok(())
+    }
+}
+
+/// `redis-cli --pipe` counterpart driving the load from the host.
+pub(super) struct HostPipe;
+
+impl HostPipe {
+    const READY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+    /// Waits until the server answers `PING` through its published port,
+    /// which may accept connections before it forwards them.
+    ///
+    /// Retries forever, the lifecycle bounds it with the [`Phase::Ready`] timeout.
+    pub(super) async fn wait_ready(addr: std::net::SocketAddr) -> anyhow::Result<()> {
+        while Self::ping(addr).await.is_err() {
+            tokio::time::sleep(Self::READY_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    async fn ping(addr: std::net::SocketAddr) -> anyhow::Result<()> {
+        let mut stream = tokio::io::BufStream::new(tokio::net::TcpStream::connect(addr).await?);
+        stream.write_all(b"PING\r\n").await?;
+        stream.flush().await?;
+        let mut reply = String::new();
+        stream.read_line(&mut reply).await?;
+        anyhow::ensure!(reply == "+PONG\r\n", "unexpected PING reply: {reply:?}");
+        Ok(())
+    }
+
+    /// Writes all commands while reading one reply per command.
+    pub(super) async fn pipe(
+        addr: std::net::SocketAddr,
+        commands: Vec<u8>,
+        commands_count: u64,
+    ) -> anyhow::Result<LoaderReport> {
+        let stream = tokio::net::TcpStream::connect(addr).await?;
+        let (reader, mut writer) = stream.into_split();
+        let write = async move {
+            writer.write_all(&commands).await?;
+            writer.flush().await?;
+            Ok::<_, anyhow::Error>(())
+        };
+        let read = async move {
+            let mut reader = tokio::io::BufReader::new(reader);
+            let mut errors = 0;
+            for _ in 0..commands_count {
+                if Self::read_reply(&mut reader).await? {
+                    errors += 1;
+                }
+            }
+            Ok::<_, anyhow::Error>(errors)
+        };
+        let ((), errors) = futures_util::try_join!(write, read)?;
+        Ok(LoaderReport {
+            rows_loaded: Some(commands_count - errors),
+            errors,
+            server_elapsed: None,
+            latency: None,
+        })
+    }
+
+    /// Reads one whole reply, nested arrays included, and tells whether it is an error.
+    async fn read_reply(reader: &mut (impl AsyncBufReadExt + Unpin)) -> anyhow::Result<bool> {
+        let mut is_error = false;
+        let mut is_first = true;
+        let mut pending = 1u64;
+        let mut line = String::new();
+        while pending > 0 {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                anyhow::bail!("redis closed the connection");
+            }
+            let (kind, value) = line.trim_end().split_at_checked(1).unwrap_or_default();
+            // Only a top level error fails the command, not an element of an array
+            is_error |= is_first && kind == "-";
+            is_first = false;
+            pending -= 1;
+            match kind {
+                "*" => pending += value.parse::<i64>()?.max(0) as u64,
+                "$" => {
+                    if let Ok(len @ 0..) = value.parse::<i64>() {
+                        // Bulk string followed by its trailing CRLF
+                        let mut bulk = vec![0; len as usize + 2];
+                        reader.read_exact(&mut bulk).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(is_error)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    /// Fake runtime whose servers are ready right away.
+    pub(in crate::backends::redis) fn ready_fake() -> crate::runtime::FakeRuntime {
+        crate::testing::ready_fake("-cli PING", "PONG\n")
+    }
+
+    pub(in crate::backends::redis) fn pipe_output(
+        errors: u64,
+        replies: u64,
+    ) -> crate::runtime::ExecOutput {
+        crate::runtime::ExecOutput {
+            stdout: format!(
+                "All data transferred. Waiting for the last reply...\n\
+                 Last reply received from server.\n\
+                 errors: {errors}, replies: {replies}\n"
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Minimal server replying `PONG` to inline `PING` and to RESP commands
+    /// with replies shaped like the real ones: a bulk string to `ZSCORE`,
+    /// arrays to `ZRANGEBYSCORE` and `EVAL`, an integer to anything else.
+    pub(in crate::backends::redis) async fn mock_redis() -> anyhow::Result<std::net::SocketAddr> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut stream = tokio::io::BufStream::new(stream);
+                    let mut line = String::new();
+                    while stream.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let reply: &[u8] = match line.trim_end().strip_prefix('*') {
+                            None if line == "PING\r\n" => b"+PONG\r\n",
+                            None => b"",
+                            Some(args) => {
+                                // Every argument is a length line and a value line
+                                let mut command = vec![];
+                                for _ in 0..args.parse::<usize>().unwrap() {
+                                    let mut arg = String::new();
+                                    stream.read_line(&mut arg).await.unwrap();
+                                    arg.clear();
+                                    stream.read_line(&mut arg).await.unwrap();
+                                    command.push(arg.trim_end().to_string());
+                                }
+                                match command[0].as_str() {
+                                    "ZSCORE" => b"$10\r\n1600000000\r\n",
+                                    "ZRANGEBYSCORE" => b"*2\r\n$2\r\ntx\r\n$10\r\n1600000000\r\n",
+                                    "EVAL" => b"*3\r\n:2\r\n$1\r\n1\r\n$1\r\n2\r\n",
+                                    _ => b":1\r\n",
+                                }
+                            }
+                        };
+                        stream.write_all(reply).await.unwrap();
+                        stream.flush().await.unwrap();
+                        line.clear();
+                    }
+                });
+            }
+        });
+        Ok(addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn host_pipe_reads_all_replies() -> anyhow::Result<()> {
+        let addr = testing::mock_redis().await?;
+        let csv_file_path = crate::testing::gen_test_csv("redis_host_pipe_reads_all_replies", 100)?;
+        let (commands, commands_count) = RespFilesManager::encode_data_file(&csv_file_path)?;
+        assert_eq!(commands_count, 100);
+        HostPipe::wait_ready(addr).await?;
+        let loader = HostPipe::pipe(addr, commands, commands_count).await?;
+        assert_eq!(loader.rows_loaded, Some(100));
+        assert_eq!(loader.errors, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_reply_consumes_nested_replies() -> anyhow::Result<()> {
+        let replies: &[u8] = b"*2\r\n*1\r\n$1\r\na\r\n$-1\r\n-ERR wrong type\r\n:1\r\n";
+        let mut reader = tokio::io::BufReader::new(replies);
+        assert!(!HostPipe::read_reply(&mut reader).await?);
+        assert!(HostPipe::read_reply(&mut reader).await?);
+        assert!(!HostPipe::read_reply(&mut reader).await?);
+        assert!(HostPipe::read_reply(&mut reader).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use db_test_model::temp::RespFilesManager;
+
+use super::pipe::{Commander, Commands, Pipe};
+use crate::docker::Load;
+use crate::workload::{Operation, WorkloadInput};
+
+/// Lua script returning the count, first and last timestamp of a sorted set.
+const AGGREGATE: &str = "local first = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES') \
+    local last = redis.call('ZRANGE', KEYS[1], -1, -1, 'WITHSCORES') \
+    return {redis.call('ZCARD', KEYS[1]), first[2], last[2]}";
+
+/// Redis command performing `operation`, shared by the pipe and the client driver.
+pub(crate) fn command(operation: &Operation) -> Vec<String> {
+    let command = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+    match operation {
+        Operation::Insert(row) => {
+            command(&["ZADD", &row.user, &row.timestamp, &row.transaction_id])
+        }
+        Operation::PointRead {
+            user,
+            transaction_id,
+        } => command(&["ZSCORE", user, transaction_id]),
+        Operation::RangeScan { user, from, to } => command(&[
+            "ZRANGEBYSCORE",
+            user,
+            &from.to_string(),
+            &to.to_string(),
+            "WITHSCORES",
+        ]),
+        Operation::Aggregate { user } => command(&["EVAL", AGGREGATE, "1", user]),
+    }
+}
+
+/// Pipes the dataset in untimed, the measured load pipes the operations
+/// of the workload.
+pub(super) async fn prepare(pipe: &Pipe<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    pipe.load_fixture(&input.file_path).await?;
+
+    let operations = input.operations()?;
+    let commands = operations.iter().map(command).collect::<Vec<_>>();
+    let stem = input
+        .file_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let commands = Commands::Encoded {
+        csv_file_path: &input.file_path,
+        name: format!("{stem}-{}", input.workload),
+        commands: RespFilesManager::encode_commands(&commands),
+        count: operations.len() as u64,
+    };
+    pipe.load(commands, Commander::QUERIES_FILE).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Redis;
+    use super::super::pipe::testing::{mock_redis, pipe_output, ready_fake};
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_loads_fixture_before_queries() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        fake.script_exec("redis-cli --pipe", pipe_output(0, 10));
+        let backend: Redis = Redis::setup(BackendConfig::new(Runtime::new(fake.clone()))).await;
+        let file_path = crate::testing::gen_test_csv("redis_prepare_loads_fixture", 10)?;
+        let bench_input = WorkloadInput::new(Workload::PointRead, file_path);
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        let cmds = fake
+            .calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                Call::StartExec { cmd, .. } => Some(cmd.join(" ")),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cmds,
+            vec![
+                "redis-cli PING",
+                "bash -c cat /tmp/items | redis-cli --pipe",
+                "bash -c cat /tmp/queries | redis-cli --pipe",
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn host_pipe_runs_every_workload() -> anyhow::Result<()> {
+        let addr = mock_redis().await?;
+        let file_path = crate::testing::gen_test_csv("redis_host_pipe_runs_every_workload", 10)?;
+        for workload in Workload::ALL {
+            let operations = WorkloadInput::new(*workload, &file_path).operations()?;
+            let commands = operations.iter().map(command).collect::<Vec<_>>();
+            let commands = RespFilesManager::encode_commands(&commands);
+            let count = operations.len() as u64;
+            let loader = super::super::pipe::HostPipe::pipe(addr, commands, count).await?;
+            assert_eq!(loader.rows_loaded, Some(count), "{workload}");
+        }
+        Ok(())
+    }
+}
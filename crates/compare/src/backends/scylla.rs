@@ -0,0 +1,308 @@
+use db_test_model::DataRow;
+
+use crate::LoaderReport;
+use crate::artifacts::Artifact;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod cqlsh;
+pub mod insert_bulk;
+
+/// Single ScyllaDB node, or any other engine speaking CQL, keeping the
+/// transactions of every user in a partition clustered by timestamp.
+///
+/// Only the exec load mode is supported, the load runs through the `cqlsh`
+/// shipped in the image.
+pub struct Scylla<V: Variant = Defaults> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+/// Engine benchmarked as a backend of its own.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    const ENGINE: Engine;
+}
+
+/// CQL server image, with the settings of a node sharing the host.
+pub struct Engine {
+    pub image_name: &'static str,
+    pub data_dir: &'static str,
+    /// Arguments of the image entrypoint, its defaults apply when empty
+    pub args: &'static [&'static str],
+    pub env: &'static [&'static str],
+    pub artifacts: &'static [Artifact],
+}
+
+macro_rules! engine {
+    ($image_name:literal, $data_dir:literal, [$($arg:literal),*], [$($env:literal),*]) => {
+        Engine {
+            image_name: $image_name,
+            data_dir: $data_dir,
+            args: &[$($arg),*],
+            env: &[$($env),*],
+            artifacts: &[
+                Artifact::Logs,
+                Artifact::Command {
+                    name: "status",
+                    script: "nodetool status",
+                },
+                Artifact::Command {
+                    name: "tablestats",
+                    script: "nodetool tablestats bench.user_transactions",
+                },
+                Artifact::Command {
+                    name: "data-dir",
+                    script: concat!("du -ab ", $data_dir),
+                },
+            ],
+        }
+    };
+}
+
+impl Engine {
+    /// One shard sharing its cores and memory with the host.
+    pub const SCYLLA: Engine = engine!(
+        "scylladb/scylla",
+        "/var/lib/scylla",
+        [
+            "--smp",
+            "1",
+            "--memory",
+            "1G",
+            "--overprovisioned",
+            "1",
+            "--developer-mode",
+            "1"
+        ],
+        []
+    );
+    pub const CASSANDRA: Engine = engine!(
+        "cassandra",
+        "/var/lib/cassandra",
+        [],
+        ["MAX_HEAP_SIZE=1G", "HEAP_NEWSIZE=256M"]
+    );
+}
+
+macro_rules! variant {
+    ($(#[$doc:meta])* $variant:ident, $name:literal, $engine:expr) => {
+        $(#[$doc])*
+        pub struct $variant;
+
+        impl Variant for $variant {
+            const NAME: &'static str = $name;
+            const CONTAINER_NAME_PREFIX: &'static str = concat!("bench-", $name);
+            const ENGINE: Engine = $engine;
+        }
+    };
+}
+
+variant!(Defaults, "scylla", Engine::SCYLLA);
+variant!(Cassandra, "cassandra", Engine::CASSANDRA);
+
+// Note: this reimport is private and exists only for consistent naming
+use Scylla as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = V::ENGINE.image_name;
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some(V::ENGINE.data_dir);
+    const ARTIFACTS: &'static [Artifact] = V::ENGINE.artifacts;
+
+    fn container_spec() -> ContainerSpec {
+        let spec = ContainerSpec::new(Self::IMAGE_NAME);
+        let spec = V::ENGINE.env.iter().fold(spec, |spec, var| spec.env(var));
+        match V::ENGINE.args {
+            [] => spec,
+            args => spec.cmd(args.iter().copied()),
+        }
+    }
+
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        cqlsh::Cqlsh::parse_load_output(output)
+    }
+
+    /// Live size of the table once its memtable is flushed.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let cmd = vec!["nodetool", "flush", "bench", "user_transactions"];
+        Container::run_cmd(config, container_name, cmd, Phase::Footprint).await?;
+        let cmd = vec!["nodetool", "tablestats", "bench.user_transactions"];
+        let output = Container::run_cmd(config, container_name, cmd, Phase::Footprint).await?;
+        let disk_bytes = output
+            .stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Space used (live): "))
+            .ok_or(anyhow::anyhow!(
+                "no table size in nodetool output: {}",
+                output.stderr
+            ))?;
+        Ok(Footprint {
+            memory_bytes: None,
+            disk_bytes: Some(disk_bytes.parse()?),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+    const LOAD_MODES: &'static [crate::LoadMode] = &[crate::LoadMode::Exec];
+    /// Scylla nodes serve CQL within a minute, while Cassandra first starts
+    /// its JVM and waits for gossip to settle, which takes minutes on a cold
+    /// image or a busy host.
+    const READY_LIMIT: Option<std::time::Duration> = Some(std::time::Duration::from_secs(300));
+
+    type Instance = crate::docker::Instance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<crate::docker::Instance> {
+        crate::docker::ensure_exec_load(V::NAME, &self.config, input)?;
+        self.containers_pool.provision().await
+    }
+
+    async fn ready(&self, instance: &mut crate::docker::Instance) -> anyhow::Result<()> {
+        let cqlsh = cqlsh::Cqlsh::new(&self.config, &instance.container_name);
+        cqlsh.wait_ready().await
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut crate::docker::Instance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let cqlsh = cqlsh::Cqlsh::new(&self.config, &instance.container_name);
+        cqlsh.migrate().await?;
+        instance.load = Some(insert_bulk::prepare(&cqlsh, input).await?);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: crate::docker::Instance) -> anyhow::Result<Self::Bencher> {
+        crate::docker::Bench::from_instance(self.config.clone(), instance)
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is stored once, in the partition of its user.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let cqlsh = &cqlsh::Cqlsh::new(&self.config, container_name);
+        let counts = async {
+            let cql = "select count(*) from bench.user_transactions";
+            let rows = cqlsh::first_value(&cqlsh.query(cql, Phase::Verify).await?)?.parse()?;
+            let cql = "select distinct user_addr from bench.user_transactions";
+            let users = cqlsh::rows_count(&cqlsh.query(cql, Phase::Verify).await?)?;
+            Ok((rows, users))
+        };
+        let trans_time = |row: &DataRow| {
+            let cql = format!(
+                "select trans_time from bench.user_transactions \
+                 where user_addr = {} and trans_hash = {} allow filtering",
+                cqlsh::literal(&row.user),
+                cqlsh::literal(&row.transaction_id)
+            );
+            async move {
+                let output = cqlsh.query(&cql, Phase::Verify).await?;
+                Ok(cqlsh::first_value(&output)?.to_owned())
+            }
+        };
+        verify::stored_rows(input, &outcome.loader, counts, trans_time).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cqlsh::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::Runtime;
+    use crate::testing::stdout;
+
+    #[test]
+    fn empty_args_keep_image_command() {
+        // Cassandra is tuned through its environment only
+        assert!(Scylla::<Cassandra>::container_spec().cmd.is_empty());
+        let scylla = Scylla::<Defaults>::container_spec();
+        assert_eq!(scylla.cmd.len(), Engine::SCYLLA.args.len());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let table = |column: &str, values: &[String]| {
+            let rows = values.iter().map(|value| format!(" {value}\n"));
+            format!(
+                "\n {column}\n-------\n{}\n({} rows)\n",
+                rows.collect::<String>(),
+                values.len()
+            )
+        };
+        let fake = ready_fake();
+        let file_path = crate::testing::gen_test_csv("scylla_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = db_test_model::sample_data_file(&bench_input.file_path, 10)?;
+        let users = (0..expected.users)
+            .map(|user| user.to_string())
+            .collect::<Vec<_>>();
+        let summary = "10 rows imported from 1 files in 0.395 seconds (0 skipped).\n";
+        fake.script_exec("COPY bench.user_transactions", stdout(summary))
+            .script_exec("count(*)", stdout(table("count", &["10".into()])))
+            .script_exec("distinct user_addr", stdout(table("user_addr", &users)))
+            .script_exec(
+                "tablestats",
+                stdout("\t\tSpace used (live): 5242\n\t\tSpace used (total): 5242\n"),
+            );
+        for row in &expected.sample {
+            let lookup = format!("trans_hash = '{}'", row.transaction_id);
+            let trans_time = table("trans_time", std::slice::from_ref(&row.timestamp));
+            fake.script_exec(&lookup, stdout(trans_time));
+        }
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Scylla = Scylla::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(5242));
+
+        // A missing row fails the verification
+        fake.script_exec("count(*)", stdout(table("count", &["9".into()])));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+}
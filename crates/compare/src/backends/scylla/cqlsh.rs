@@ -0,0 +1,204 @@
+use super::Container;
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// One partition per user, its transactions clustered in time order.
+const SCHEMA: &str = "\
+create keyspace if not exists bench
+with replication = {'class': 'SimpleStrategy', 'replication_factor': 1};
+create table if not exists bench.user_transactions (
+    user_addr text,
+    trans_time bigint,
+    trans_hash text,
+    primary key ((user_addr), trans_time, trans_hash)
+) with clustering order by (trans_time asc, trans_hash asc);";
+
+/// Runs CQL through `cqlsh` inside a started container.
+pub(super) struct Cqlsh<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+}
+
+impl<'a> Cqlsh<'a> {
+    /// Seconds a statement may take, counting the whole table included.
+    const REQUEST_TIMEOUT: &'static str = "600";
+    /// Nodes take tens of seconds to start, there is no point in polling
+    /// them as often as other servers.
+    const READY_POLL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    pub(super) fn new(config: &'a crate::BackendConfig, container_name: &'a str) -> Self {
+        Cqlsh {
+            config,
+            container_name,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// `cqlsh` running the `;` separated `cql` on the local node.
+    pub(super) fn command(cql: &str) -> Vec<&str> {
+        vec![
+            "cqlsh",
+            "--request-timeout",
+            Self::REQUEST_TIMEOUT,
+            "-e",
+            cql,
+        ]
+    }
+
+    /// Runs `cql` as part of `phase`, returning its table output.
+    pub(super) async fn query(&self, cql: &str, phase: Phase) -> anyhow::Result<String> {
+        let cmd = Self::command(cql);
+        let output = Container::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// `cqlsh` reports errors as `<stdin>:N:… Error …` on stderr.
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !output.stderr.contains("Error"),
+            "cqlsh failed: {}",
+            output.stderr.trim()
+        );
+        Ok(())
+    }
+
+    /// Waits until the node accepts CQL clients, well after the container
+    /// is up.
+    pub(super) async fn wait_ready(&self) -> anyhow::Result<()> {
+        loop {
+            let cmd = Self::command("select release_version from system.local");
+            let output = Container::poll_cmd(self.config, self.container_name, cmd, Phase::Ready);
+            if output.await?.stdout.contains("release_version") {
+                return Ok(());
+            }
+            tokio::time::sleep(Self::READY_POLL).await;
+        }
+    }
+
+    /// Creates the keyspace and the table.
+    pub(super) async fn migrate(&self) -> anyhow::Result<()> {
+        self.query(SCHEMA, Phase::LoadFixture)
+            .await
+            .map_err(|err| anyhow::anyhow!("schema creation failed: {err}"))?;
+        Ok(())
+    }
+
+    /// `COPY FROM` ends with an `N rows imported from M files in S seconds
+    /// (K skipped).` summary, and reports the rows it gave up on with
+    /// `Failed to process N rows`.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        let rows_loaded = output
+            .stdout
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().split_once(" rows imported from "))
+            .ok_or(anyhow::anyhow!(
+                "no summary in cqlsh output: {}",
+                output.stderr
+            ))?
+            .0;
+        let errors = output
+            .stderr
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("Failed to process "))
+            .and_then(|failed| failed.split_whitespace().next())
+            .map(str::parse)
+            .transpose()?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded.parse()?),
+            errors: errors.unwrap_or(0),
+            ..Default::default()
+        })
+    }
+}
+
+/// First column of the first row of a `cqlsh` table.
+pub(super) fn first_value(output: &str) -> anyhow::Result<&str> {
+    let value = output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with('-'))
+        .nth(1)
+        .and_then(|row| row.split('|').next())
+        .ok_or(anyhow::anyhow!("no rows in: {output:?}"))?;
+    Ok(value.trim())
+}
+
+/// Rows of a `cqlsh` table, from its `(N rows)` footer.
+pub(super) fn rows_count(output: &str) -> anyhow::Result<u64> {
+    let rows = output
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix('('))
+        .and_then(|footer| footer.split_once(" rows)"))
+        .ok_or(anyhow::anyhow!("no rows count in: {output:?}"))?
+        .0;
+    Ok(rows.parse()?)
+}
+
+/// Quotes `value` as a CQL string literal.
+pub(super) fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+pub(super) mod testing {
+    use crate::runtime::FakeRuntime;
+
+    /// Fake runtime whose nodes are ready right away.
+    pub(in crate::backends::scylla) fn ready_fake() -> FakeRuntime {
+        crate::testing::ready_fake(
+            "system.local",
+            "\n release_version\n-----------------\n           3.0.8\n\n(1 rows)\n",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let output = ExecOutput {
+            stdout: "Using 7 child processes\n\n\
+                     Starting copy of bench.user_transactions with columns \
+                     [user_addr, trans_time, trans_hash].\n\
+                     Processed: 498 rows; Rate:     950 rows/s; Avg. rate:    1066 rows/s\n\
+                     498 rows imported from 1 files in 0.467 seconds (0 skipped).\n"
+                .into(),
+            stderr: "Failed to import 2 rows: ParseError - invalid literal for int(), \
+                     given up without retries\n\
+                     Failed to process 2 rows; failed rows written to import_bench.err\n"
+                .into(),
+            ..Default::default()
+        };
+        let loader = Cqlsh::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(498));
+        assert_eq!(loader.errors, 2);
+        assert!(Cqlsh::parse_load_output(&ExecOutput::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_tables() -> anyhow::Result<()> {
+        let output = "\n count\n-------\n    10\n\n(1 rows)\n";
+        assert_eq!(first_value(output)?, "10");
+        assert_eq!(rows_count(output)?, 1);
+        assert!(first_value("\n count\n-------\n").is_err());
+        assert_eq!(literal("it's"), "'it''s'");
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use db_test_model::temp::CsvFilesManager;
+
+use super::Container;
+use super::cqlsh::{Cqlsh, literal};
+use crate::docker::{Docker, Load};
+use crate::workload::WorkloadInput;
+
+/// Measured load reading the uploaded data file with `COPY FROM`.
+pub(super) async fn prepare(cqlsh: &Cqlsh<'_>, input: &WorkloadInput) -> anyhow::Result<Load> {
+    // Upload data file
+    let config = cqlsh.config();
+    let container_name = cqlsh.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = CsvFilesManager::tar_data_file(&input.file_path, dst_file)?;
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
+
+    // Prepare load exec
+    let copy = format!(
+        "COPY bench.user_transactions (user_addr, trans_time, trans_hash) FROM {} \
+         WITH HEADER = false",
+        literal(&data_file)
+    );
+    let command = Cqlsh::command(&copy);
+    let exec_id = Container::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Scylla;
+    use super::super::cqlsh::testing::ready_fake;
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{ExecOutput, Runtime, fake::Call};
+    use crate::workload::Workload;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_creates_table_and_uploads_dataset() -> anyhow::Result<()> {
+        let fake = ready_fake();
+        fake.script_exec(
+            "COPY bench.user_transactions",
+            ExecOutput {
+                stdout: "10 rows imported from 1 files in 0.395 seconds (0 skipped).\n".into(),
+                ..Default::default()
+            },
+        );
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Scylla = Scylla::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("scylla_prepare_uploads_dataset", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-scylla-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert!(execs[1].starts_with("cqlsh --request-timeout 600 -e create keyspace"));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "cqlsh --request-timeout 600 -e COPY bench.user_transactions \
+                 (user_addr, trans_time, trans_hash) FROM '/tmp/items' WITH HEADER = false"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
+
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        Ok(())
+    }
+}
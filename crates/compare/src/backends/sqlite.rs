@@ -1 +1,215 @@
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::footprint::Footprint;
+use crate::runtime::ContainerSpec;
+use crate::timeout::Phase;
+use crate::verify;
+use crate::workload::{Workload, WorkloadInput};
+
+mod client;
 pub mod insert_bulk;
+pub mod pragmas;
+mod sqlite3;
+
+pub use pragmas::{Defaults, MATRIX, Pragmas, Variant};
+
+/// SQLite database file in [`Docker::DATA_DIR`], written by the `sqlite3`
+/// shell of an otherwise idle container with the settings of `V`.
+///
+/// There is no server to connect to, so the host load mode is not supported.
+/// The driver load mode opens the database file from the benchmark process,
+/// which needs the data dir on [bind storage](crate::storage::Storage::Bind).
+pub struct Sqlite<V: Variant = Defaults> {
+    config: crate::BackendConfig,
+    containers_pool: crate::docker::Pool<Self>,
+    _variant: std::marker::PhantomData<V>,
+}
+
+// Note: this reimport is private and exists only for consistent naming
+use Sqlite as Backend;
+
+/// Runs the execs which do not depend on the variant.
+type Container = Backend;
+
+impl<V: Variant> crate::docker::Docker for Backend<V> {
+    const IMAGE_NAME: &'static str = "keinos/sqlite3";
+    const CONTAINER_NAME_PREFIX: &'static str = V::CONTAINER_NAME_PREFIX;
+    const DATA_DIR: Option<&'static str> = Some("/data");
+
+    /// Keeps the container running between the `sqlite3` execs, as root so
+    /// that they can write to a data dir mounted by the runtime.
+    fn container_spec() -> ContainerSpec {
+        ContainerSpec::new(Self::IMAGE_NAME)
+            .cmd(["tail", "-f", "/dev/null"])
+            .user("root")
+    }
+
+    fn parse_load_output(output: &crate::runtime::ExecOutput) -> anyhow::Result<LoaderReport> {
+        sqlite3::Sqlite3::parse_load_output(output)
+    }
+
+    /// Size of the pages in use by the tables and indexes, the database file
+    /// less its free pages.
+    async fn measure_footprint(
+        config: &crate::BackendConfig,
+        container_name: &str,
+    ) -> anyhow::Result<Footprint> {
+        let sqlite3 = sqlite3::Sqlite3::new(config, container_name);
+        let sql = "select (page_count - freelist_count) * page_size \
+                   from pragma_page_count(), pragma_freelist_count(), pragma_page_size()";
+        let disk_bytes = sqlite3.query(sql, Phase::Footprint).await?;
+        Ok(Footprint {
+            memory_bytes: None,
+            disk_bytes: Some(disk_bytes.trim().parse()?),
+        })
+    }
+}
+
+impl<V: Variant> crate::Backend for Backend<V> {
+    const NAME: &'static str = V::NAME;
+    const WORKLOADS: &'static [Workload] = &[Workload::BulkInsert];
+    const LOAD_MODES: &'static [crate::LoadMode] =
+        &[crate::LoadMode::Exec, crate::LoadMode::Driver];
+
+    type Instance = crate::docker::Instance;
+    type Bencher = crate::docker::Bench<Self>;
+
+    async fn setup(config: crate::BackendConfig) -> Self {
+        let containers_pool = crate::docker::Pool::new(config.clone());
+        Backend {
+            config,
+            containers_pool,
+            _variant: std::marker::PhantomData,
+        }
+    }
+
+    fn config(&self) -> &crate::BackendConfig {
+        &self.config
+    }
+
+    async fn provision(&self, input: &WorkloadInput) -> anyhow::Result<crate::docker::Instance> {
+        anyhow::ensure!(
+            self.config.load_mode != crate::LoadMode::Host,
+            "sqlite has no client port to load from the host"
+        );
+        anyhow::ensure!(input.clients == 1, "sqlite loads have a single client");
+        self.containers_pool.provision().await
+    }
+
+    async fn ready(&self, instance: &mut crate::docker::Instance) -> anyhow::Result<()> {
+        let sqlite3 = sqlite3::Sqlite3::new(&self.config, &instance.container_name);
+        sqlite3.create_data_dir().await
+    }
+
+    async fn load_fixture(
+        &self,
+        instance: &mut crate::docker::Instance,
+        input: &WorkloadInput,
+    ) -> anyhow::Result<()> {
+        let sqlite3 = sqlite3::Sqlite3::new(&self.config, &instance.container_name);
+        sqlite3.migrate(V::PRAGMAS).await?;
+        let load = match self.config.load_mode {
+            crate::LoadMode::Driver => client::prepare(&sqlite3, input, V::PRAGMAS).await?,
+            crate::LoadMode::Exec | crate::LoadMode::Host => {
+                insert_bulk::prepare(&sqlite3, input, V::PRAGMAS).await?
+            }
+        };
+        instance.load = Some(load);
+        Ok(())
+    }
+
+    fn bencher(&self, instance: crate::docker::Instance) -> anyhow::Result<Self::Bencher> {
+        crate::docker::Bench::from_instance(self.config.clone(), instance)
+    }
+
+    async fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> anyhow::Result<Footprint> {
+        Self::guarded_footprint(&self.config, container_guard).await
+    }
+
+    /// Every row is stored once with its timestamp.
+    async fn verify(
+        &self,
+        input: &WorkloadInput,
+        outcome: &crate::BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let container_name = outcome.container_guard.container_name();
+        let sqlite3 = &sqlite3::Sqlite3::new(&self.config, container_name);
+        let query = |sql: String| async move { sqlite3.query(&sql, Phase::Verify).await };
+        verify::sql_rows(input, &outcome.loader, '|', query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Backend as _;
+    use crate::runtime::{FakeRuntime, Runtime};
+    use crate::testing::stdout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_compares_stored_rows() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let file_path = crate::testing::gen_test_csv("sqlite_verify_compares_stored_rows", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let expected = crate::testing::script_sql_rows(&fake, &bench_input, '|')?;
+        fake.script_exec(".import", stdout("10\n"))
+            .script_exec("pragma_page_count", stdout("8192\n"));
+        let config = crate::BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Sqlite = Sqlite::setup(config).await;
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        backend.verify(&bench_input, &outcome).await?;
+        let footprint = backend.footprint(&outcome.container_guard).await?;
+        assert_eq!(footprint.disk_bytes, Some(8192));
+
+        // A missing row fails the verification
+        fake.script_exec("count(*)", stdout(format!("9|{}\n", expected.users)));
+        assert!(backend.verify(&bench_input, &outcome).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn driver_load_writes_bind_mounted_database() -> anyhow::Result<()> {
+        let host_dir = std::env::temp_dir().join("db-test-compare/sqlite_driver_load_storage");
+        let _ = std::fs::remove_dir_all(&host_dir);
+        // The fake runtime runs no `sqlite3`, the schema is created up front
+        let data_dir = host_dir.join("bench-sqlite-0");
+        std::fs::create_dir_all(&data_dir)?;
+        rusqlite::Connection::open(data_dir.join("bench.db"))?.execute_batch(
+            "create table user_transactions \
+             (user_addr text not null, trans_time bigint not null, trans_hash text not null)",
+        )?;
+        let storage = crate::storage::Storage::Bind { host_dir };
+        let config = crate::BackendConfig::new(Runtime::new(FakeRuntime::new()))
+            .with_storage(storage)
+            .with_load_mode(crate::LoadMode::Driver);
+        let backend: Sqlite = Sqlite::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("sqlite_driver_load", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let outcome = crate::registry::DynBackend::prepare(&backend, &bench_input)
+            .await?
+            .bench
+            .await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        assert!(outcome.loader.latency.is_some());
+        let connection = rusqlite::Connection::open(data_dir.join("bench.db"))?;
+        let rows: u64 =
+            connection.query_row("select count(*) from user_transactions", [], |row| {
+                row.get(0)
+            })?;
+        assert_eq!(rows, 10);
+
+        // Without bind storage the database file cannot be reached
+        let config = crate::BackendConfig::new(Runtime::new(FakeRuntime::new()))
+            .with_load_mode(crate::LoadMode::Driver);
+        let backend: Sqlite = Sqlite::setup(config).await;
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await;
+        assert!(prepared.is_err());
+        Ok(())
+    }
+}
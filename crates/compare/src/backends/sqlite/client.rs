@@ -0,0 +1,36 @@
+use super::Container;
+use super::pragmas::Pragmas;
+use super::sqlite3::Sqlite3;
+use crate::docker::{Docker, Load};
+use crate::driver::{self, SqliteDriver};
+use crate::timeout::Phase;
+use crate::workload::WorkloadInput;
+
+/// Measured load inserting every row through a [`SqliteDriver`] opened on
+/// the bind mounted database file, with the connection settings of `pragmas`.
+///
+/// rusqlite blocks, so the driver runs on a blocking thread once the
+/// measurement starts.
+pub(super) async fn prepare(
+    sqlite3: &Sqlite3<'_>,
+    input: &WorkloadInput,
+    pragmas: Option<Pragmas>,
+) -> anyhow::Result<Load> {
+    let database_file = sqlite3.host_database_file()?;
+    // Created by root inside the container, the driver writes its journal next to it
+    let data_dir = sqlite3.data_dir();
+    let cmd = vec!["chmod", "-R", "a+rwX", &data_dir];
+    let config = sqlite3.config();
+    Container::run_cmd(config, sqlite3.container_name(), cmd, Phase::LoadFixture).await?;
+
+    let operations = input.client_operations()?;
+    let driver = SqliteDriver::open(&database_file)?;
+    if let Some(pragmas) = pragmas {
+        driver.execute_batch(&pragmas.connection().join(";\n"))?;
+    }
+    Ok(Load::Host(Box::pin(async move {
+        let handle = tokio::runtime::Handle::current();
+        let load = move || handle.block_on(driver::run(driver, operations));
+        tokio::task::spawn_blocking(load).await?
+    })))
+}
@@ -1,37 +1,181 @@
-use crate::docker::Docker;
+use db_test_model::DataRow;
+use db_test_model::temp::CsvFilesManager;
 
-pub struct SqliteInsertBulk {
-    containers_pool: crate::docker::Pool<Self>,
+use super::Container;
+use super::pragmas::Pragmas;
+use super::sqlite3::Sqlite3;
+use crate::docker::{Docker, Load};
+use crate::verify::literal;
+use crate::workload::WorkloadInput;
+
+/// Measured load importing the uploaded data file with `.import`, or
+/// reading the statements inserting it in batches of `pragmas`, then
+/// printing how many rows were inserted.
+pub(super) async fn prepare(
+    sqlite3: &Sqlite3<'_>,
+    input: &WorkloadInput,
+    pragmas: Option<Pragmas>,
+) -> anyhow::Result<Load> {
+    // Upload data file
+    let config = sqlite3.config();
+    let container_name = sqlite3.container_name();
+    let dst_file = std::path::Path::new(crate::docker::DATA_FILE);
+    let tar_path = match pragmas {
+        None => CsvFilesManager::tar_data_file(&input.file_path, dst_file)?,
+        Some(pragmas) => {
+            let rows = db_test_model::read_data_file(&input.file_path)?;
+            let encoded = encode(&rows, pragmas.batch_size)?;
+            let stem = input.file_path.file_stem().unwrap_or_default();
+            let name = format!("{}-sqlite-b{}", stem.display(), pragmas.batch_size);
+            CsvFilesManager::tar_encoded(&input.file_path, &name, &encoded, dst_file)?
+        }
+    };
+    let data_file = crate::docker::upload_data_file(config, container_name, tar_path).await?;
+
+    // Prepare load exec
+    let mut commands = vec![];
+    match pragmas {
+        None => commands.push(format!(".import --csv {data_file} user_transactions")),
+        Some(pragmas) => {
+            commands.extend(pragmas.connection());
+            commands.push(format!(".read {data_file}"));
+        }
+    }
+    commands.push("select total_changes()".into());
+    let commands = commands.iter().map(String::as_str).collect::<Vec<_>>();
+    let database_file = sqlite3.database_file();
+    let command = Sqlite3::command(&database_file, &commands);
+    let exec_id = Container::create_exec(&config.runtime, container_name, command).await?;
+    Ok(Load::Exec(exec_id))
 }
 
-impl crate::docker::Docker for SqliteInsertBulk {
-    const IMAGE_NAME: &'static str = "sqlite";
-    const CONTAINER_NAME_PREFIX: &'static str = "bench-sqlite-insert-bulk";
+/// Script inserting the rows one by one, `batch_size` rows per transaction.
+fn encode(rows: &[DataRow], batch_size: usize) -> anyhow::Result<Vec<u8>> {
+    let mut encoded = vec![];
+    for batch in rows.chunks(batch_size) {
+        encoded.extend_from_slice(b"begin;\n");
+        for row in batch {
+            let timestamp: i64 = row
+                .timestamp
+                .parse()
+                .map_err(|err| anyhow::anyhow!("invalid timestamp of {row:?}: {err}"))?;
+            let insert = format!(
+                "insert into user_transactions (user_addr, trans_time, trans_hash) \
+                 values ({}, {timestamp}, {});\n",
+                literal(&row.user),
+                literal(&row.transaction_id)
+            );
+            encoded.extend_from_slice(insert.as_bytes());
+        }
+        encoded.extend_from_slice(b"commit;\n");
+    }
+    Ok(encoded)
 }
 
-impl crate::Backend for SqliteInsertBulk {
-    type Input = crate::InsertBulkBenchInput;
+#[cfg(test)]
+mod tests {
+    use super::super::Sqlite;
+    use super::*;
+    use crate::Backend as _;
+    use crate::BackendConfig;
+    use crate::runtime::{ExecOutput, FakeRuntime, Runtime, fake::Call};
+    use crate::workload::Workload;
 
-    async fn setup(docker: &bollard::Docker) -> Self {
-        todo!()
-    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_creates_schema_and_uploads_dataset() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        fake.script_exec(
+            ".import",
+            ExecOutput {
+                stdout: "10\n".into(),
+                ..Default::default()
+            },
+        );
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Sqlite = Sqlite::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("sqlite_prepare_uploads_dataset", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+
+        let container_name: Box<str> = "bench-sqlite-0".into();
+        let execs = crate::testing::execs(&fake);
+        assert_eq!(execs[0], "mkdir -p /data");
+        assert!(execs[1].starts_with("sqlite3 -bail /data/bench.db create table"));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "sqlite3 -bail /data/bench.db \
+                 .import --csv /tmp/items user_transactions select total_changes()"
+            )
+        );
+        assert!(fake.calls().contains(&Call::UploadArchive {
+            container_name,
+            dest_path: "/tmp".into(),
+            entries: vec!["items".into()],
+        }));
 
-    #[allow(refining_impl_trait)]
-    async fn prepare(&self, docker: &bollard::Docker) -> SqliteInsertBulkBench {
-        todo!()
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        Ok(())
     }
-}
 
-pub struct SqliteInsertBulkBench {}
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tuned_variants_read_batched_statements() -> anyhow::Result<()> {
+        struct Tuned;
 
-impl crate::Bench for SqliteInsertBulkBench {
-    type Input = crate::InsertBulkBenchInput;
+        impl super::super::Variant for Tuned {
+            const NAME: &'static str = "sqlite-tuned";
+            const CONTAINER_NAME_PREFIX: &'static str = "bench-sqlite-tuned";
+            const PRAGMAS: Option<Pragmas> = Some(Pragmas {
+                journal_mode: "wal",
+                synchronous: "normal",
+                page_size: 65536,
+                cache_size_kib: 2048,
+                batch_size: 4,
+            });
+        }
 
-    async fn run(&mut self, input: Self::Input) -> crate::docker::ContainerGuard {
-        todo!()
+        let fake = FakeRuntime::new();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let backend: Sqlite<Tuned> = Sqlite::setup(config).await;
+        let file_path = crate::testing::gen_test_csv("sqlite_tuned_variants", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let _prepared = crate::registry::DynBackend::prepare(&backend, &bench_input).await?;
+        let execs = crate::testing::execs(&fake);
+        assert!(execs[1].starts_with(
+            "sqlite3 -bail /data/bench.db pragma page_size = 65536;\n\
+             pragma journal_mode = wal;\ncreate table"
+        ));
+        assert_eq!(
+            execs.last().map(String::as_str),
+            Some(
+                "sqlite3 -bail /data/bench.db pragma journal_mode = wal \
+                 pragma synchronous = normal pragma cache_size = -2048 \
+                 .read /tmp/items select total_changes()"
+            )
+        );
+        Ok(())
     }
 
-    async fn prepare(&mut self, input: &Self::Input) -> () {
-        todo!()
+    #[test]
+    fn encode_batches_in_transactions() -> anyhow::Result<()> {
+        let row = |user: &str, timestamp: &str| DataRow {
+            user: user.into(),
+            timestamp: timestamp.into(),
+            transaction_id: format!("{user}{timestamp}"),
+        };
+        let rows = [row("a", "1"), row("b", "2"), row("c", "3")];
+        let encoded = String::from_utf8(encode(&rows, 2)?)?;
+        assert_eq!(
+            encoded,
+            "begin;\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) values ('a', 1, 'a1');\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) values ('b', 2, 'b2');\n\
+             commit;\n\
+             begin;\n\
+             insert into user_transactions (user_addr, trans_time, trans_hash) values ('c', 3, 'c3');\n\
+             commit;\n"
+        );
+        Ok(())
     }
 }
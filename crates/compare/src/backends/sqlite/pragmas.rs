@@ -0,0 +1,116 @@
+use super::Sqlite;
+use crate::registry::BackendFactory;
+
+/// Settings of one variant of the tuning matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pragmas {
+    /// `delete`, `wal`, `memory` or `off`
+    pub journal_mode: &'static str,
+    /// `off`, `normal` or `full`
+    pub synchronous: &'static str,
+    /// Bytes per page, set before the schema is created
+    pub page_size: u32,
+    /// Page cache of the loading connection
+    pub cache_size_kib: u32,
+    /// Rows inserted per transaction
+    pub batch_size: usize,
+}
+
+impl Pragmas {
+    /// Statements applying the settings of the loading connection.
+    pub(super) fn connection(&self) -> [String; 3] {
+        [
+            format!("pragma journal_mode = {}", self.journal_mode),
+            format!("pragma synchronous = {}", self.synchronous),
+            format!("pragma cache_size = -{}", self.cache_size_kib),
+        ]
+    }
+
+    /// Statements which only apply to the database file before anything
+    /// is written to it.
+    pub(super) fn database(&self) -> [String; 2] {
+        [
+            format!("pragma page_size = {}", self.page_size),
+            format!("pragma journal_mode = {}", self.journal_mode),
+        ]
+    }
+}
+
+/// Settings benchmarked as a backend of their own.
+pub trait Variant: Send + Sync + 'static {
+    const NAME: &str;
+    const CONTAINER_NAME_PREFIX: &str;
+    /// `None` keeps the `sqlite3` defaults and imports the data file in a
+    /// single transaction with `.import`
+    const PRAGMAS: Option<Pragmas>;
+}
+
+/// `sqlite3` defaults.
+pub struct Defaults;
+
+impl Variant for Defaults {
+    const NAME: &'static str = "sqlite";
+    const CONTAINER_NAME_PREFIX: &'static str = "bench-sqlite";
+    const PRAGMAS: Option<Pragmas> = None;
+}
+
+/// Backends of every combination of one value per axis, in order.
+macro_rules! matrix {
+    // Pairs the next combination with every value of the axis
+    (@product [$($done:tt)*] [$combination:tt $($pending:tt)*] [$($value:tt)*] $($axes:tt)*) => {
+        matrix!(@product [$($done)* $([$combination $value])*] [$($pending)*] [$($value)*] $($axes)*)
+    };
+    (@product [$($done:tt)*] [] [$($value:tt)*] $($axes:tt)*) => {
+        matrix!(@product [] [$($done)*] $($axes)*)
+    };
+    (@product [] [$($combination:tt)*]) => {
+        &[$(matrix!(@variant $combination)),*]
+    };
+    (@variant [[[[[[] $journal_mode:ident] $synchronous:ident] $page_size:literal] $cache_size_kib:literal] $batch_size:literal]) => {{
+        struct Combination;
+
+        impl Variant for Combination {
+            const NAME: &'static str =
+                matrix!(@name $journal_mode $synchronous $page_size $cache_size_kib $batch_size);
+            const CONTAINER_NAME_PREFIX: &'static str = concat!(
+                "bench-",
+                matrix!(@name $journal_mode $synchronous $page_size $cache_size_kib $batch_size)
+            );
+            const PRAGMAS: Option<Pragmas> = Some(Pragmas {
+                journal_mode: stringify!($journal_mode),
+                synchronous: stringify!($synchronous),
+                page_size: $page_size,
+                cache_size_kib: $cache_size_kib,
+                batch_size: $batch_size,
+            });
+        }
+
+        BackendFactory::of::<Sqlite<Combination>>()
+    }};
+    (@name $journal_mode:ident $synchronous:ident $page_size:literal $cache_size_kib:literal $batch_size:literal) => {
+        concat!(
+            "sqlite-",
+            stringify!($journal_mode),
+            "-",
+            stringify!($synchronous),
+            "-p",
+            $page_size,
+            "-c",
+            $cache_size_kib,
+            "k-b",
+            $batch_size
+        )
+    };
+    ($($axis:tt)+) => {
+        matrix!(@product [] [[]] $($axis)+)
+    };
+}
+
+/// Tuning matrix, named `sqlite-{journal_mode}-{synchronous}-p{page_size}-c{cache_size}k-b{batch_size}`.
+pub const MATRIX: &[BackendFactory] = matrix!(
+    [delete wal memory off]
+    [off normal full]
+    [4096 65536]
+    [2048 262144]
+    [1000 100000]
+);
@@ -0,0 +1,165 @@
+use super::Container;
+use super::pragmas::Pragmas;
+use crate::LoaderReport;
+use crate::docker::Docker;
+use crate::timeout::Phase;
+
+/// Same table as the Postgres migrations, the index covers the hash as
+/// SQLite has no included columns.
+const SCHEMA: &str = "\
+create table if not exists user_transactions (
+    user_addr text not null,
+    trans_time bigint not null,
+    trans_hash text not null
+);
+create index if not exists transactions_index on user_transactions
+(user_addr, trans_time, trans_hash);";
+
+/// Runs SQL through `sqlite3` on the database file of a started container.
+pub(super) struct Sqlite3<'a> {
+    config: &'a crate::BackendConfig,
+    container_name: &'a str,
+}
+
+impl<'a> Sqlite3<'a> {
+    const DATABASE_FILE: &'static str = "bench.db";
+
+    pub(super) fn new(config: &'a crate::BackendConfig, container_name: &'a str) -> Self {
+        Sqlite3 {
+            config,
+            container_name,
+        }
+    }
+
+    pub(super) fn config(&self) -> &'a crate::BackendConfig {
+        self.config
+    }
+
+    pub(super) fn container_name(&self) -> &'a str {
+        self.container_name
+    }
+
+    /// Path of `path` in the container as seen by its execs.
+    pub(super) fn container_path(&self, path: &std::path::Path) -> String {
+        let path = self
+            .config
+            .runtime
+            .container_path(self.container_name, path);
+        path.display().to_string()
+    }
+
+    pub(super) fn data_dir(&self) -> String {
+        let data_dir = Container::DATA_DIR.unwrap_or("/");
+        self.container_path(std::path::Path::new(data_dir))
+    }
+
+    /// Path of the database file in the data dir.
+    pub(super) fn database_file(&self) -> String {
+        let data_dir = Container::DATA_DIR.unwrap_or("/");
+        let database_file = std::path::Path::new(data_dir).join(Self::DATABASE_FILE);
+        self.container_path(&database_file)
+    }
+
+    /// Path of the database file on the host, only known when the data dir
+    /// is bind mounted.
+    pub(super) fn host_database_file(&self) -> anyhow::Result<std::path::PathBuf> {
+        let crate::storage::Storage::Bind { host_dir } = &self.config.storage else {
+            anyhow::bail!("the database file is only reachable from the host on bind storage");
+        };
+        let host_path = host_dir.join(self.container_name);
+        Ok(host_path.join(Self::DATABASE_FILE))
+    }
+
+    /// `sqlite3` running `commands` in order on `database_file`, stopping at
+    /// the first error.
+    pub(super) fn command<'c>(database_file: &'c str, commands: &[&'c str]) -> Vec<&'c str> {
+        let mut cmd = vec!["sqlite3", "-bail", database_file];
+        cmd.extend(commands);
+        cmd
+    }
+
+    /// Runs `sql` as part of `phase`, returning its `|` separated rows.
+    pub(super) async fn query(&self, sql: &str, phase: Phase) -> anyhow::Result<String> {
+        let database_file = self.database_file();
+        let cmd = Self::command(&database_file, &[sql]);
+        let output = Container::run_cmd(self.config, self.container_name, cmd, phase).await?;
+        Self::ensure_no_error(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// `sqlite3` reports errors, and the rows `.import` skips, on stderr only.
+    fn ensure_no_error(output: &crate::runtime::ExecOutput) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            output.stderr.trim().is_empty(),
+            "sqlite3 failed: {}",
+            output.stderr.trim()
+        );
+        Ok(())
+    }
+
+    /// Creates the data dir, which is only there when mounted by the storage.
+    pub(super) async fn create_data_dir(&self) -> anyhow::Result<()> {
+        let data_dir = self.data_dir();
+        let cmd = vec!["mkdir", "-p", &data_dir];
+        let output =
+            Container::run_cmd(self.config, self.container_name, cmd, Phase::Ready).await?;
+        Self::ensure_no_error(&output)
+    }
+
+    /// Creates the schema in a new database file, with the page size and
+    /// journal mode of `pragmas`.
+    pub(super) async fn migrate(&self, pragmas: Option<Pragmas>) -> anyhow::Result<()> {
+        let mut sql = vec![];
+        if let Some(pragmas) = pragmas {
+            sql.extend(pragmas.database());
+        }
+        sql.push(SCHEMA.to_owned());
+        self.query(&sql.join(";\n"), Phase::LoadFixture)
+            .await
+            .map_err(|err| anyhow::anyhow!("schema creation failed: {err}"))?;
+        Ok(())
+    }
+
+    /// Rows changed by the load, printed by its last statement.
+    pub(super) fn parse_load_output(
+        output: &crate::runtime::ExecOutput,
+    ) -> anyhow::Result<LoaderReport> {
+        Self::ensure_no_error(output)?;
+        let rows_loaded = output
+            .stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .ok_or(anyhow::anyhow!("no row count in sqlite3 output"))?;
+        Ok(LoaderReport {
+            rows_loaded: Some(rows_loaded.parse()?),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ExecOutput;
+
+    #[test]
+    fn parse_load_summary() -> anyhow::Result<()> {
+        let output = ExecOutput {
+            stdout: "500\n".into(),
+            ..Default::default()
+        };
+        let loader = Sqlite3::parse_load_output(&output)?;
+        assert_eq!(loader.rows_loaded, Some(500));
+
+        let output = ExecOutput {
+            stdout: "499\n".into(),
+            stderr: "/tmp/items:3: expected 3 columns but found 2 - filling the rest with NULL\n"
+                .into(),
+            ..Default::default()
+        };
+        assert!(Sqlite3::parse_load_output(&output).is_err());
+        assert!(Sqlite3::parse_load_output(&ExecOutput::default()).is_err());
+        Ok(())
+    }
+}
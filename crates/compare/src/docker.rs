@@ -1,12 +1,10 @@
-use futures_util::{StreamExt, TryFutureExt};
-use tokio_util::io::ReaderStream;
+use crate::artifacts::Artifact;
+use crate::footprint::{Footprint, data_dir_size};
+use crate::runtime::{ContainerSpec, ExecOutput, Runtime};
+use crate::timeout::{Phase, Timeouts};
+use crate::{BackendConfig, BenchOutcome, LoaderReport};
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct ContainerId(pub(crate) Box<str>);
-
-#[derive(Debug, Clone)]
-pub struct ExecId(pub(crate) Box<str>);
+pub use crate::runtime::{ContainerId, ExecId};
 
 #[derive(Debug)]
 pub(crate) struct ContainerInfo {
@@ -18,132 +16,162 @@ pub(crate) struct ContainerInfo {
 pub trait Docker: Send + Sync + 'static {
     const IMAGE_NAME: &str;
     const CONTAINER_NAME_PREFIX: &str;
+    /// Directory the database keeps its files in, placed on the configured
+    /// [`Storage`](crate::storage::Storage)
+    const DATA_DIR: Option<&str> = None;
+    /// Port clients connect to, published when the load [runs from the host](crate::LoadMode::from_host)
+    const CLIENT_PORT: Option<u16> = None;
+    /// Collected before the container is removed when an artifacts directory is configured
+    const ARTIFACTS: &[Artifact] = &[Artifact::Logs];
+
+    fn container_spec() -> ContainerSpec {
+        ContainerSpec::new(Self::IMAGE_NAME)
+    }
 
     fn create_container(
-        docker: &bollard::Docker,
+        config: &BackendConfig,
         container_name: &str,
-    ) -> impl Future<Output = anyhow::Result<bollard::secret::ContainerCreateResponse>> + Send {
+    ) -> impl Future<Output = anyhow::Result<ContainerId>> + Send {
         async move {
-            let options = bollard::container::CreateContainerOptions {
-                name: container_name,
-                platform: None,
-            };
-            let config = bollard::container::Config {
-                image: Some(Self::IMAGE_NAME),
-                ..Default::default()
-            };
-            let container = docker.create_container(Some(options), config).await?;
-            Ok(container)
+            let mut spec = Self::container_spec();
+            if let Some(data_dir) = Self::DATA_DIR {
+                let mount = config.storage.mount(container_name, data_dir);
+                spec.mounts.extend(mount);
+            }
+            if let Some(port) = Self::CLIENT_PORT
+                && config.load_mode.from_host()
+            {
+                spec.ports.push(port);
+            }
+            config.runtime.create_container(container_name, &spec).await
         }
     }
 
     fn start_container(
-        docker: &bollard::Docker,
+        config: &BackendConfig,
         container_name: Box<str>,
     ) -> impl Future<Output = anyhow::Result<ContainerGuard>> + Send {
         async move {
-            let options = bollard::container::StartContainerOptions {
-                ..Default::default()
-            };
-            docker
-                .start_container::<String>(&container_name, Some(options))
-                .await?;
-            Ok(ContainerGuard::new(container_name, docker))
+            config.runtime.start_container(&container_name).await?;
+            let container_guard = ContainerGuard::new(container_name, &config.runtime)
+                .with_timeouts(&config.timeouts);
+            match &config.artifacts_dir {
+                Some(run_dir) => Ok(container_guard.with_artifacts(run_dir, Self::ARTIFACTS)),
+                None => Ok(container_guard),
+            }
         }
     }
 
     fn create_exec(
-        docker: &bollard::Docker,
+        runtime: &Runtime,
         container_name: &str,
         cmd: Vec<&str>,
     ) -> impl Future<Output = anyhow::Result<ExecId>> + Send {
-        async move {
-            let config = bollard::exec::CreateExecOptions {
-                attach_stdout: Some(true),
-                attach_stderr: Some(true),
-                cmd: Some(cmd),
-                ..Default::default()
-            };
-            let exec = docker.create_exec(container_name, config).await?;
-            Ok(ExecId(exec.id.into_boxed_str()))
-        }
+        async move { runtime.create_exec(container_name, cmd).await }
     }
 
+    /// Runs a prepared exec as part of `phase`, see [`Timeouts::exec`](crate::timeout::Timeouts::exec).
     fn start_exec(
-        docker: &bollard::Docker,
+        config: &BackendConfig,
         exec_id: &ExecId,
-    ) -> impl Future<Output = anyhow::Result<bollard::exec::StartExecResults>> + Send {
+        phase: Phase,
+    ) -> impl Future<Output = anyhow::Result<ExecOutput>> + Send {
         async move {
-            let options = bollard::exec::StartExecOptions {
-                detach: false,
-                ..Default::default()
-            };
-            let attach = docker.start_exec(&exec_id.0, Some(options)).await?;
-            Ok(attach)
+            let output = config
+                .timeouts
+                .exec(&config.runtime, exec_id, phase)
+                .await?;
+            #[cfg(test)]
+            println!("{}{}", output.stdout, output.stderr);
+            Ok(output)
         }
     }
 
-    fn attached_exec(attach: bollard::exec::StartExecResults) -> impl Future<Output = ()> + Send {
+    /// Runs `cmd` as part of `phase`, failing when it exits with a non-zero status.
+    fn run_cmd(
+        config: &BackendConfig,
+        container_name: &str,
+        cmd: Vec<&str>,
+        phase: Phase,
+    ) -> impl Future<Output = anyhow::Result<ExecOutput>> + Send {
         async move {
-            if let bollard::exec::StartExecResults::Attached { mut output, .. } = attach {
-                #[cfg(test)]
-                while let Some(Ok(msg)) = output.next().await {
-                    println!("{msg}");
-                }
-                #[cfg(not(test))]
-                while let Some(Ok(_)) = output.next().await {}
-            }
+            let output = Self::poll_cmd(config, container_name, cmd, phase).await?;
+            output.ensure_success()?;
+            Ok(output)
         }
     }
 
-    fn run_cmd(
-        docker: &bollard::Docker,
+    /// Runs `cmd` like [`Docker::run_cmd`] whatever its exit status, for
+    /// readiness checks which fail until the server is up.
+    fn poll_cmd(
+        config: &BackendConfig,
         container_name: &str,
         cmd: Vec<&str>,
-    ) -> impl Future<Output = anyhow::Result<()>> + Send {
+        phase: Phase,
+    ) -> impl Future<Output = anyhow::Result<ExecOutput>> + Send {
+        async move {
+            let exec_id = Self::create_exec(&config.runtime, container_name, cmd).await?;
+            Self::start_exec(config, &exec_id, phase).await
+        }
+    }
+
+    /// Extracts what the load tool printed about the measured load.
+    fn parse_load_output(output: &ExecOutput) -> anyhow::Result<LoaderReport> {
+        let _ = output;
+        Ok(LoaderReport::default())
+    }
+
+    /// [`Docker::measure_footprint`] of the container `container_guard` holds.
+    fn guarded_footprint(
+        config: &BackendConfig,
+        container_guard: &ContainerGuard,
+    ) -> impl Future<Output = anyhow::Result<Footprint>> + Send {
+        Self::measure_footprint(config, container_guard.container_name())
+    }
+
+    /// Measures the loaded data, by default the size of [`Docker::DATA_DIR`].
+    fn measure_footprint(
+        config: &BackendConfig,
+        container_name: &str,
+    ) -> impl Future<Output = anyhow::Result<Footprint>> + Send {
         async move {
-            let exec_id = Self::create_exec(docker, container_name, cmd).await?;
-            let attach = Self::start_exec(docker, &exec_id).await?;
-            Self::attached_exec(attach).await;
-            Ok(())
+            let disk_bytes = match Self::DATA_DIR {
+                Some(data_dir) => Some(data_dir_size(config, container_name, data_dir).await?),
+                None => None,
+            };
+            Ok(Footprint {
+                disk_bytes,
+                ..Default::default()
+            })
         }
     }
 
     fn upload_large_file(
-        docker: &bollard::Docker,
+        runtime: &Runtime,
         container_name: &str,
         file_path: std::path::PathBuf,
         dest_path: std::path::PathBuf,
     ) -> impl Future<Output = anyhow::Result<()>> + Send {
         async move {
-            let file = tokio::fs::File::open(file_path)
-                .map_ok(ReaderStream::new)
-                .try_flatten_stream()
-                .map(|x| x.expect("failed to stream file"));
-            let options = bollard::container::UploadToContainerOptions {
-                path: dest_path.display().to_string(),
-                ..Default::default()
-            };
-            docker
-                .upload_to_container_streaming(container_name, Some(options), file)
-                .await?;
-            Ok(())
+            runtime
+                .upload_archive(container_name, file_path, dest_path)
+                .await
         }
     }
 }
 
 pub(crate) struct Pool<D: Docker> {
     running_containers: std::sync::atomic::AtomicU32,
-    docker: bollard::Docker,
+    config: BackendConfig,
     // Consume generic param
     _docker_trait: std::marker::PhantomData<D>,
 }
 
 impl<D: Docker> Pool<D> {
-    pub fn new(docker: bollard::Docker) -> Self {
+    pub fn new(config: BackendConfig) -> Self {
         Pool {
             running_containers: 0.into(),
-            docker,
+            config,
             _docker_trait: std::marker::PhantomData,
         }
     }
@@ -155,92 +183,507 @@ impl<D: Docker> Pool<D> {
                 .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
             format!("{}-{}", D::CONTAINER_NAME_PREFIX, container_n).into_boxed_str()
         };
-        let container = D::create_container(&self.docker, &container_name).await?;
-        let container_id = ContainerId(container.id.into_boxed_str());
+        let container_id = D::create_container(&self.config, &container_name).await?;
         Ok(ContainerInfo {
             container_name,
             container_id,
         })
     }
+
+    /// Creates and starts the container of an [`Instance`].
+    pub async fn provision(&self) -> anyhow::Result<Instance> {
+        let ContainerInfo { container_name, .. } = self.create_container().await?;
+        let container_guard = D::start_container(&self.config, container_name.clone()).await?;
+        Ok(Instance {
+            container_name,
+            container_guard,
+            load: None,
+        })
+    }
+}
+
+/// Fails unless `config` and `input` describe a single client
+/// [`LoadMode::Exec`](crate::LoadMode::Exec) load, the only one backends
+/// loading through the tools of their image run.
+pub fn ensure_exec_load(
+    name: &str,
+    config: &BackendConfig,
+    input: &crate::workload::WorkloadInput,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        config.load_mode == crate::LoadMode::Exec,
+        "{name} loads run through the tools inside the container"
+    );
+    anyhow::ensure!(input.clients == 1, "{name} loads have a single client");
+    Ok(())
 }
 
-pub struct Bench<D: Docker, I> {
-    docker: bollard::Docker,
-    exec_id: crate::docker::ExecId,
+/// Where [`upload_data_file`] puts the dataset, or the script a load
+/// encoded from it.
+pub const DATA_FILE: &str = "/tmp/items";
+
+/// Uploads the archive at `tar_path`, holding [`DATA_FILE`], within the
+/// [`Phase::LoadFixture`] timeout.
+///
+/// Returns the path of the uploaded file as seen by the execs of `container_name`.
+pub async fn upload_data_file(
+    config: &BackendConfig,
+    container_name: &str,
+    tar_path: std::path::PathBuf,
+) -> anyhow::Result<String> {
+    let data_file = std::path::Path::new(DATA_FILE);
+    let dst_path = data_file.parent().unwrap_or(std::path::Path::new("/"));
+    let upload = config
+        .runtime
+        .upload_archive(container_name, tar_path, dst_path.to_owned());
+    config.timeouts.within(Phase::LoadFixture, upload).await?;
+    let data_file = config.runtime.container_path(container_name, data_file);
+    Ok(data_file.display().to_string())
+}
+
+/// Started container, with the measured load once the fixture is loaded.
+pub struct Instance {
+    pub(crate) container_name: Box<str>,
+    pub(crate) container_guard: ContainerGuard,
+    pub(crate) load: Option<Load>,
+}
+
+/// Future driving the load from the benchmark process.
+pub type HostLoad = futures_util::future::BoxFuture<'static, anyhow::Result<LoaderReport>>;
+
+/// Measured part of a [`Bench`].
+pub enum Load {
+    /// Prepared exec inside the database container
+    Exec(ExecId),
+    /// Client running on the host, see [`LoadMode::from_host`](crate::LoadMode::from_host)
+    Host(HostLoad),
+}
+
+pub struct Bench<D: Docker> {
+    config: BackendConfig,
+    load: Load,
     container_guard: crate::docker::ContainerGuard,
-    // Consume generic params
+    // Consume generic param
     _docker_trait: std::marker::PhantomData<D>,
-    _bench_input: std::marker::PhantomData<I>,
 }
 
-impl<D: Docker, I> Bench<D, I> {
+impl<D: Docker> Bench<D> {
     pub fn new(
-        docker: bollard::Docker,
-        exec_id: crate::docker::ExecId,
+        config: BackendConfig,
+        load: Load,
         container_guard: crate::docker::ContainerGuard,
     ) -> Self {
         Bench {
-            docker,
-            exec_id,
+            config,
+            load,
             container_guard,
             _docker_trait: std::marker::PhantomData,
-            _bench_input: std::marker::PhantomData,
         }
     }
-}
 
-impl<D: Docker, I> crate::Bencher for Bench<D, I> {
-    type Input = I;
+    /// Bench running the load prepared on `instance`.
+    pub fn from_instance(config: BackendConfig, instance: Instance) -> anyhow::Result<Self> {
+        let load = instance.load.ok_or(anyhow::anyhow!(
+            "fixture of {} is not loaded",
+            instance.container_name
+        ))?;
+        Ok(Bench::new(config, load, instance.container_guard))
+    }
+}
 
-    fn run(self) -> impl Future<Output = anyhow::Result<crate::docker::ContainerGuard>> + Send {
+impl<D: Docker> crate::Bencher for Bench<D> {
+    fn run(self) -> impl Future<Output = anyhow::Result<BenchOutcome>> + Send {
         let Bench {
-            docker,
-            exec_id,
+            config,
+            load,
             container_guard,
             ..
         } = self;
         async move {
-            let attach = D::start_exec(&docker, &exec_id).await?;
-            D::attached_exec(attach).await;
-            Ok(container_guard)
+            let loader = match load {
+                Load::Exec(exec_id) => {
+                    let output = D::start_exec(&config, &exec_id, Phase::Measure).await?;
+                    output.ensure_success()?;
+                    D::parse_load_output(&output)?
+                }
+                Load::Host(host_load) => config.timeouts.within(Phase::Measure, host_load).await?,
+            };
+            Ok(BenchOutcome {
+                loader,
+                container_guard,
+            })
         }
     }
 }
 
 pub struct ContainerGuard {
     container_name: Option<Box<str>>,
-    docker: bollard::Docker,
+    runtime: Runtime,
+    /// Bounds the teardown, which also runs on drop
+    timeouts: Timeouts,
+    /// Destination directory and artifacts to collect before removal
+    artifacts: Option<(std::path::PathBuf, &'static [Artifact])>,
 }
 
 impl ContainerGuard {
-    pub fn new(container_name: Box<str>, docker: &bollard::Docker) -> Self {
+    pub fn new(container_name: Box<str>, runtime: &Runtime) -> Self {
         let container_name = Some(container_name);
-        let docker = docker.clone();
+        let runtime = runtime.clone();
         ContainerGuard {
             container_name,
-            docker,
+            runtime,
+            timeouts: Timeouts::default(),
+            artifacts: None,
         }
     }
+
+    /// Bounds the teardown by the [`Phase::Teardown`] limit of `timeouts`
+    /// instead of the default one.
+    pub fn with_timeouts(mut self, timeouts: &Timeouts) -> Self {
+        self.timeouts = timeouts.clone();
+        self
+    }
+
+    pub fn container_name(&self) -> &str {
+        // IMPLEMENATION SAFETY:
+        // container_name is always Some until the drop occurs.
+        self.container_name.as_deref().unwrap()
+    }
+
+    /// Collects `artifacts` into `{run_dir}/{container_name}` on drop.
+    pub fn with_artifacts(
+        mut self,
+        run_dir: &std::path::Path,
+        artifacts: &'static [Artifact],
+    ) -> Self {
+        // IMPLEMENATION SAFETY:
+        // container_name is always Some until the drop occurs.
+        let dest_dir = run_dir.join(self.container_name.as_deref().unwrap());
+        self.artifacts = Some((dest_dir, artifacts));
+        self
+    }
 }
 
-impl Drop for ContainerGuard {
-    fn drop(&mut self) {
-        let docker = self.docker.clone();
+impl ContainerGuard {
+    /// Collects the artifacts and removes the container now, reporting
+    /// failures instead of panicking like the drop does.
+    pub async fn remove(mut self) -> anyhow::Result<()> {
         // IMPLEMENATION SAFETY:
         // container_name is always Some until the drop occurs.
         let container_name = self.container_name.take().unwrap();
+        let artifacts = self.artifacts.take();
+        Self::teardown(&self.runtime, &self.timeouts, &container_name, artifacts).await
+    }
+
+    /// Collecting the artifacts and removing the container are each bounded
+    /// by the [`Phase::Teardown`] limit, so that a wedged server cannot hang
+    /// the run.
+    async fn teardown(
+        runtime: &Runtime,
+        timeouts: &Timeouts,
+        container_name: &str,
+        artifacts: Option<(std::path::PathBuf, &'static [Artifact])>,
+    ) -> anyhow::Result<()> {
+        if let Some((dest_dir, artifacts)) = artifacts {
+            let collect =
+                crate::artifacts::collect(runtime, timeouts, container_name, artifacts, &dest_dir);
+            if let Err(err) = timeouts.within(Phase::Teardown, collect).await {
+                eprintln!("cannot collect artifacts of {container_name}: {err:#}");
+            }
+        }
+        let remove = runtime.remove_container(container_name);
+        timeouts
+            .within(Phase::Teardown, remove)
+            .await
+            .map_err(|err| anyhow::anyhow!("cannot remove container: {container_name}: {err}"))
+    }
+}
+
+impl Drop for ContainerGuard {
+    fn drop(&mut self) {
+        // Already removed by `ContainerGuard::remove`
+        let Some(container_name) = self.container_name.take() else {
+            return;
+        };
+        let runtime = self.runtime.clone();
+        let timeouts = self.timeouts.clone();
+        let artifacts = self.artifacts.take();
         tokio::task::block_in_place(|| {
-            let runtime = tokio::runtime::Handle::current();
-            runtime.block_on(async move {
-                let options = bollard::container::RemoveContainerOptions {
-                    force: true,
-                    ..Default::default()
-                };
-                docker
-                    .remove_container(&container_name, Some(options))
-                    .await
-                    .expect(format!("cannot remove container: {container_name}").as_str());
+            let handle = tokio::runtime::Handle::current();
+            handle.block_on(async move {
+                let teardown = Self::teardown(&runtime, &timeouts, &container_name, artifacts);
+                if let Err(err) = teardown.await {
+                    panic!("{err}");
+                }
             })
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{FakeRuntime, fake::Call};
+
+    struct TestDocker;
+
+    impl Docker for TestDocker {
+        const IMAGE_NAME: &str = "test-image";
+        const CONTAINER_NAME_PREFIX: &str = "test-container";
+        const DATA_DIR: Option<&str> = Some("/data");
+        const CLIENT_PORT: Option<u16> = Some(4242);
+    }
+
+    #[tokio::test]
+    async fn pool_numbers_containers() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        let pool = Pool::<TestDocker>::new(config);
+        let first = pool.create_container().await?;
+        let second = pool.create_container().await?;
+        assert_eq!(first.container_name.as_ref(), "test-container-0");
+        assert_eq!(second.container_name.as_ref(), "test-container-1");
+        assert_eq!(
+            fake.container_spec("test-container-0")
+                .unwrap()
+                .image
+                .as_ref(),
+            "test-image"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pool_mounts_data_dir_on_storage() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let storage = crate::storage::Storage::Volume {
+            prefix: "bench".into(),
+        };
+        let config = BackendConfig::new(Runtime::new(fake.clone())).with_storage(storage);
+        let pool = Pool::<TestDocker>::new(config);
+        pool.create_container().await?;
+        assert_eq!(
+            fake.container_spec("test-container-0").unwrap().mounts,
+            vec![crate::runtime::Mount::Volume {
+                target: "/data".into(),
+                name: "bench-test-container-0".into(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pool_publishes_client_port_in_host_mode() -> anyhow::Result<()> {
+        for (load_mode, published) in [
+            (crate::LoadMode::Exec, false),
+            (crate::LoadMode::Host, true),
+            (crate::LoadMode::Driver, true),
+        ] {
+            let fake = FakeRuntime::new();
+            let runtime = Runtime::new(fake.clone());
+            let config = BackendConfig::new(runtime.clone()).with_load_mode(load_mode);
+            let pool = Pool::<TestDocker>::new(config);
+            let ContainerInfo { container_name, .. } = pool.create_container().await?;
+            let addr = runtime.host_port(&container_name, 4242).await;
+            assert_eq!(addr.is_ok(), published);
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn guard_removes_container_on_drop() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let runtime = Runtime::new(fake.clone());
+        let config = BackendConfig::new(runtime.clone());
+        TestDocker::create_container(&config, "guarded").await?;
+        let guard = TestDocker::start_container(&config, "guarded".into()).await?;
+        assert_eq!(fake.containers(), vec!["guarded".into()]);
+        drop(guard);
+        assert!(fake.containers().is_empty());
+        assert_eq!(
+            fake.calls().last(),
+            Some(&Call::RemoveContainer {
+                container_name: "guarded".into()
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn guard_removed_explicitly_is_not_removed_again() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        TestDocker::create_container(&config, "removed").await?;
+        let guard = TestDocker::start_container(&config, "removed".into()).await?;
+        guard.remove().await?;
+        assert!(fake.containers().is_empty());
+        let removals = fake
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, Call::RemoveContainer { .. }))
+            .count();
+        assert_eq!(removals, 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn guard_collects_artifacts_before_removal() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let run_dir = std::env::temp_dir().join("db-test-compare/guard_collects_artifacts");
+        let _ = std::fs::remove_dir_all(&run_dir);
+        let config = BackendConfig::new(Runtime::new(fake.clone())).with_artifacts_dir(&run_dir);
+        TestDocker::create_container(&config, "collected").await?;
+        let guard = TestDocker::start_container(&config, "collected".into()).await?;
+        drop(guard);
+        let calls = fake.calls();
+        assert_eq!(
+            calls[calls.len() - 2..],
+            [
+                Call::Logs {
+                    container_name: "collected".into()
+                },
+                Call::RemoveContainer {
+                    container_name: "collected".into()
+                },
+            ]
+        );
+        assert!(run_dir.join("collected/server.log").exists());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hung_artifact_command_does_not_hang_the_drop() -> anyhow::Result<()> {
+        const ARTIFACTS: &[Artifact] = &[Artifact::Command {
+            name: "stats",
+            script: "nodetool tablestats",
+        }];
+        let fake = FakeRuntime::new();
+        fake.hang_exec("nodetool");
+        let run_dir = std::env::temp_dir().join("db-test-compare/hung_artifact_command");
+        let limit = std::time::Duration::from_millis(10);
+        let timeouts = Timeouts::default().with_exec(limit);
+        let config = BackendConfig::new(Runtime::new(fake.clone()));
+        TestDocker::create_container(&config, "wedged").await?;
+        config.runtime.start_container("wedged").await?;
+        let guard = ContainerGuard::new("wedged".into(), &config.runtime)
+            .with_timeouts(&timeouts)
+            .with_artifacts(&run_dir, ARTIFACTS);
+        drop(guard);
+        let calls = fake.calls();
+        assert!(matches!(
+            calls[calls.len() - 2..],
+            [Call::KillExec { .. }, Call::RemoveContainer { .. }]
+        ));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn run_cmd_returns_scripted_output() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        let expected = ExecOutput {
+            stdout: "PONG\n".into(),
+            ..Default::default()
+        };
+        let refused = ExecOutput {
+            stderr: "Could not connect to Redis\n".into(),
+            exit_code: Some(1),
+            ..Default::default()
+        };
+        fake.script_exec("PING", expected.clone())
+            .script_exec("INFO", refused.clone())
+            .fail_exec("SHUTDOWN", "connection lost");
+        let runtime = Runtime::new(fake.clone());
+        let config = BackendConfig::new(runtime.clone());
+        TestDocker::create_container(&config, "scripted").await?;
+        let _guard = TestDocker::start_container(&config, "scripted".into()).await?;
+        let ping = vec!["redis-cli", "PING"];
+        let output = TestDocker::run_cmd(&config, "scripted", ping, Phase::Ready).await?;
+        assert_eq!(output, expected);
+        let info = vec!["redis-cli", "INFO"];
+        let output = TestDocker::poll_cmd(&config, "scripted", info.clone(), Phase::Ready).await?;
+        assert_eq!(output, refused);
+        let info = TestDocker::run_cmd(&config, "scripted", info, Phase::Ready);
+        assert!(info.await.is_err());
+        let shutdown = vec!["redis-cli", "SHUTDOWN"];
+        let shutdown = TestDocker::run_cmd(&config, "scripted", shutdown, Phase::Measure);
+        assert!(shutdown.await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn footprint_measures_data_dir() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        fake.script_exec(
+            "du -sb -L /data",
+            ExecOutput {
+                stdout: "4096\t/data\n".into(),
+                ..Default::default()
+            },
+        );
+        let runtime = Runtime::new(fake);
+        let config = BackendConfig::new(runtime.clone());
+        TestDocker::create_container(&config, "measured").await?;
+        let guard = TestDocker::start_container(&config, "measured".into()).await?;
+        let footprint = TestDocker::measure_footprint(&config, guard.container_name()).await?;
+        assert_eq!(footprint.disk_bytes, Some(4096));
+        assert_eq!(footprint.memory_bytes, None);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hung_load_times_out_in_measure_phase() -> anyhow::Result<()> {
+        use crate::Bencher as _;
+
+        let fake = FakeRuntime::new();
+        fake.hang_exec("load");
+        let limit = std::time::Duration::from_millis(10);
+        let timeouts = crate::timeout::Timeouts::default().with_phase(Phase::Measure, limit);
+        let config = BackendConfig::new(Runtime::new(fake.clone())).with_timeouts(timeouts);
+        TestDocker::create_container(&config, "hung").await?;
+        let guard = TestDocker::start_container(&config, "hung".into()).await?;
+        let exec_id = TestDocker::create_exec(&config.runtime, "hung", vec!["load"]).await?;
+        let bench = Bench::<TestDocker>::new(config, Load::Exec(exec_id), guard);
+        let err = bench.run().await.err().unwrap();
+        let timeout = err.downcast_ref::<crate::timeout::Timeout>();
+        assert_eq!(timeout.map(|timeout| timeout.phase), Some(Phase::Measure));
+        // Killed first, then removed together with the dropped guard
+        let calls = fake.calls();
+        assert!(matches!(
+            calls[calls.len() - 2..],
+            [Call::KillExec { .. }, Call::RemoveContainer { .. }]
+        ));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_load_fails_the_bench() -> anyhow::Result<()> {
+        use crate::Bencher as _;
+
+        let fake = FakeRuntime::new();
+        fake.script_exec(
+            "load",
+            ExecOutput {
+                stderr: "disk full\n".into(),
+                exit_code: Some(1),
+                ..Default::default()
+            },
+        );
+        let config = BackendConfig::new(Runtime::new(fake));
+        TestDocker::create_container(&config, "failed").await?;
+        let guard = TestDocker::start_container(&config, "failed".into()).await?;
+        let exec_id = TestDocker::create_exec(&config.runtime, "failed", vec!["load"]).await?;
+        let bench = Bench::<TestDocker>::new(config, Load::Exec(exec_id), guard);
+        let err = bench.run().await.err().unwrap();
+        assert!(err.to_string().contains("disk full"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn exec_requires_running_container() -> anyhow::Result<()> {
+        let runtime = Runtime::new(FakeRuntime::new());
+        TestDocker::create_container(&BackendConfig::new(runtime.clone()), "stopped").await?;
+        let exec = TestDocker::create_exec(&runtime, "stopped", vec!["true"]).await;
+        assert!(exec.is_err());
+        Ok(())
+    }
+}
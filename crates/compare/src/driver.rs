@@ -0,0 +1,123 @@
+use crate::LoaderReport;
+use crate::latency::Histogram;
+use crate::workload::Operation;
+
+pub mod postgres;
+pub mod redis;
+pub mod sqlite;
+
+pub use postgres::PostgresDriver;
+pub use redis::RedisDriver;
+pub use sqlite::SqliteDriver;
+
+/// Native client issuing workload operations, see [`LoadMode::Driver`](crate::LoadMode::Driver).
+pub trait Driver: Send {
+    /// Executes one operation, reading its whole result.
+    fn execute(&mut self, operation: &Operation)
+    -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// Executes `operations` in order, recording the latency of every one.
+///
+/// Failed operations are counted as errors, like the load tools do, rather
+/// than aborting the run.
+pub async fn run(driver: impl Driver, operations: Vec<Operation>) -> anyhow::Result<LoaderReport> {
+    let operations_count = operations.len() as u64;
+    let (histogram, errors) = run_client(driver, operations).await;
+    Ok(report(operations_count, &histogram, errors))
+}
+
+/// Splits `operations` into contiguous chunks, one per driver, executed
+/// by concurrent tasks on the current runtime, see [`run`].
+pub async fn run_concurrent<D>(
+    drivers: Vec<D>,
+    operations: Vec<Operation>,
+) -> anyhow::Result<LoaderReport>
+where
+    D: Driver + 'static,
+{
+    anyhow::ensure!(!drivers.is_empty(), "no client to run the operations");
+    let operations_count = operations.len() as u64;
+    let chunk_size = operations.len().div_ceil(drivers.len()).max(1);
+    let mut chunks = operations.chunks(chunk_size).map(<[Operation]>::to_vec);
+    let clients = drivers
+        .into_iter()
+        .map(|driver| tokio::spawn(run_client(driver, chunks.next().unwrap_or_default())))
+        .collect::<Vec<_>>();
+    let mut histogram = Histogram::new();
+    let mut errors = 0;
+    for client in clients {
+        let (client_histogram, client_errors) = client.await?;
+        histogram.add(&client_histogram);
+        errors += client_errors;
+    }
+    Ok(report(operations_count, &histogram, errors))
+}
+
+async fn run_client(mut driver: impl Driver, operations: Vec<Operation>) -> (Histogram, u64) {
+    let mut histogram = Histogram::new();
+    let mut errors = 0;
+    for operation in &operations {
+        if histogram.time(driver.execute(operation)).await.is_err() {
+            errors += 1;
+        }
+    }
+    (histogram, errors)
+}
+
+fn report(operations_count: u64, histogram: &Histogram, errors: u64) -> LoaderReport {
+    LoaderReport {
+        rows_loaded: Some(operations_count - errors),
+        errors,
+        server_elapsed: None,
+        latency: histogram.percentiles(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails every operation on the given user.
+    struct RejectingDriver(&'static str);
+
+    impl Driver for RejectingDriver {
+        async fn execute(&mut self, operation: &Operation) -> anyhow::Result<()> {
+            match operation {
+                Operation::Aggregate { user } if user == self.0 => anyhow::bail!("rejected"),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn run_counts_failed_operations() -> anyhow::Result<()> {
+        let operations = ["a", "b", "a"]
+            .map(|user| Operation::Aggregate { user: user.into() })
+            .to_vec();
+        let loader = run(RejectingDriver("b"), operations).await?;
+        assert_eq!(loader.rows_loaded, Some(2));
+        assert_eq!(loader.errors, 1);
+        assert!(loader.latency.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_concurrent_splits_operations() -> anyhow::Result<()> {
+        let operations = (0..10)
+            .map(|i| Operation::Aggregate {
+                user: ["a", "b"][i % 2].into(),
+            })
+            .collect::<Vec<_>>();
+        let drivers = (0..4).map(|_| RejectingDriver("b")).collect();
+        let loader = run_concurrent(drivers, operations).await?;
+        assert_eq!(loader.rows_loaded, Some(5));
+        assert_eq!(loader.errors, 5);
+        assert!(
+            run_concurrent(Vec::<RejectingDriver>::new(), vec![])
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+}
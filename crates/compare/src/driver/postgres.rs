@@ -0,0 +1,66 @@
+use sqlx::Connection;
+
+use crate::workload::Operation;
+
+/// Postgres client issuing prepared statements on the `user_transactions` table.
+pub struct PostgresDriver(sqlx::PgConnection);
+
+impl PostgresDriver {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        Ok(PostgresDriver(sqlx::PgConnection::connect(url).await?))
+    }
+}
+
+impl super::Driver for PostgresDriver {
+    async fn execute(&mut self, operation: &Operation) -> anyhow::Result<()> {
+        let connection = &mut self.0;
+        match operation {
+            Operation::Insert(row) => {
+                sqlx::query(
+                    "insert into user_transactions (trans_time, user_addr, trans_hash) \
+                     values ($1, $2, $3)",
+                )
+                .bind(row.timestamp.parse::<i64>()?)
+                .bind(&row.user)
+                .bind(&row.transaction_id)
+                .execute(connection)
+                .await?;
+            }
+            Operation::PointRead {
+                user,
+                transaction_id,
+            } => {
+                sqlx::query(
+                    "select trans_time from user_transactions \
+                     where user_addr = $1 and trans_hash = $2",
+                )
+                .bind(user)
+                .bind(transaction_id)
+                .fetch_all(connection)
+                .await?;
+            }
+            Operation::RangeScan { user, from, to } => {
+                sqlx::query(
+                    "select trans_time, trans_hash from user_transactions \
+                     where user_addr = $1 \
+                     and trans_time between $2 and $3",
+                )
+                .bind(user)
+                .bind(*from as i64)
+                .bind(*to as i64)
+                .fetch_all(connection)
+                .await?;
+            }
+            Operation::Aggregate { user } => {
+                sqlx::query(
+                    "select count(*), min(trans_time), max(trans_time) from user_transactions \
+                     where user_addr = $1",
+                )
+                .bind(user)
+                .fetch_one(connection)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
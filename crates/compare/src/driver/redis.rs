@@ -0,0 +1,24 @@
+use crate::workload::Operation;
+
+/// Redis client on a multiplexed connection.
+pub struct RedisDriver(::redis::aio::MultiplexedConnection);
+
+impl RedisDriver {
+    pub async fn connect(addr: std::net::SocketAddr) -> anyhow::Result<Self> {
+        let client = ::redis::Client::open(format!("redis://{addr}"))?;
+        Ok(RedisDriver(
+            client.get_multiplexed_async_connection().await?,
+        ))
+    }
+}
+
+impl super::Driver for RedisDriver {
+    async fn execute(&mut self, operation: &Operation) -> anyhow::Result<()> {
+        let command = crate::backends::redis::queries::command(operation);
+        let (name, args) = command.split_first().expect("commands have a name");
+        let mut cmd = ::redis::cmd(name);
+        cmd.arg(args);
+        cmd.query_async::<::redis::Value>(&mut self.0).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::workload::Operation;
+
+/// SQLite connection issuing cached statements on the `user_transactions` table.
+///
+/// SQLite runs in process, so every operation executes synchronously on
+/// the calling task, which should be a blocking thread.
+pub struct SqliteDriver(rusqlite::Connection);
+
+impl SqliteDriver {
+    pub fn open(path: &std::path::Path) -> anyhow::Result<Self> {
+        Ok(SqliteDriver(rusqlite::Connection::open(path)?))
+    }
+
+    /// Runs `;` separated statements, e.g. the pragmas of the connection.
+    pub fn execute_batch(&self, sql: &str) -> anyhow::Result<()> {
+        Ok(self.0.execute_batch(sql)?)
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> anyhow::Result<()> {
+        let mut statement = self.0.prepare_cached(sql)?;
+        let mut rows = statement.query(params)?;
+        while rows.next()?.is_some() {}
+        Ok(())
+    }
+}
+
+impl super::Driver for SqliteDriver {
+    async fn execute(&mut self, operation: &Operation) -> anyhow::Result<()> {
+        match operation {
+            Operation::Insert(row) => {
+                let mut statement = self.0.prepare_cached(
+                    "insert into user_transactions (trans_time, user_addr, trans_hash) \
+                     values (?1, ?2, ?3)",
+                )?;
+                let timestamp: i64 = row.timestamp.parse()?;
+                statement.execute((timestamp, &row.user, &row.transaction_id))?;
+                Ok(())
+            }
+            Operation::PointRead {
+                user,
+                transaction_id,
+            } => self.query(
+                "select trans_time from user_transactions \
+                 where user_addr = ?1 and trans_hash = ?2",
+                (user, transaction_id),
+            ),
+            Operation::RangeScan { user, from, to } => self.query(
+                "select trans_time, trans_hash from user_transactions \
+                 where user_addr = ?1 and trans_time between ?2 and ?3",
+                (user, *from as i64, *to as i64),
+            ),
+            Operation::Aggregate { user } => self.query(
+                "select count(*), min(trans_time), max(trans_time) from user_transactions \
+                 where user_addr = ?1",
+                (user,),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Driver, run};
+    use super::*;
+    use crate::workload::{Workload, WorkloadInput};
+
+    #[tokio::test]
+    async fn sqlite_driver_runs_every_workload() -> anyhow::Result<()> {
+        let file_path = crate::testing::gen_test_csv("sqlite_driver_runs_every_workload", 50)?;
+        let db_path = file_path.with_file_name("data.db");
+        let driver = SqliteDriver::open(&db_path)?;
+        driver.0.execute_batch(
+            "create table user_transactions \
+             (trans_time integer not null, user_addr text not null, trans_hash text not null)",
+        )?;
+        let input = WorkloadInput::new(Workload::BulkInsert, &file_path);
+        let loader = run(driver, input.client_operations()?).await?;
+        assert_eq!(loader.rows_loaded, Some(50));
+        assert_eq!(loader.errors, 0);
+
+        for workload in Workload::ALL {
+            let operations = WorkloadInput::new(*workload, &file_path).client_operations()?;
+            let count = operations.len() as u64;
+            let loader = run(SqliteDriver::open(&db_path)?, operations).await?;
+            assert_eq!(loader.rows_loaded, Some(count), "{workload}");
+        }
+        let mut driver = SqliteDriver::open(&db_path)?;
+        driver.0.execute_batch("drop table user_transactions")?;
+        let operation = Operation::Aggregate { user: "a".into() };
+        assert!(driver.execute(&operation).await.is_err());
+        Ok(())
+    }
+}
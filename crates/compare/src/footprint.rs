@@ -0,0 +1,91 @@
+use crate::BackendConfig;
+use crate::timeout::Phase;
+
+/// Space a backend uses once the dataset is loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Footprint {
+    /// Memory held by the data, as reported by the server
+    pub memory_bytes: Option<u64>,
+    /// Size of the data directory
+    pub disk_bytes: Option<u64>,
+}
+
+impl Footprint {
+    pub fn memory_per_row(&self, rows: u64) -> Option<f64> {
+        Self::per_row(self.memory_bytes, rows)
+    }
+
+    pub fn disk_per_row(&self, rows: u64) -> Option<f64> {
+        Self::per_row(self.disk_bytes, rows)
+    }
+
+    fn per_row(bytes: Option<u64>, rows: u64) -> Option<f64> {
+        bytes
+            .filter(|_| rows > 0)
+            .map(|bytes| bytes as f64 / rows as f64)
+    }
+}
+
+/// Size in bytes of `path` inside the container, as reported by `du`.
+pub async fn data_dir_size(
+    config: &BackendConfig,
+    container_name: &str,
+    path: &str,
+) -> anyhow::Result<u64> {
+    let runtime = &config.runtime;
+    let path = runtime.container_path(container_name, std::path::Path::new(path));
+    let path = path.display().to_string();
+    let exec_id = runtime
+        .create_exec(container_name, vec!["du", "-sb", "-L", &path])
+        .await?;
+    let output = config
+        .timeouts
+        .exec(runtime, &exec_id, Phase::Footprint)
+        .await?;
+    parse_du(&output.stdout)
+}
+
+/// Parses `du -sb` output: `<bytes>\t<path>`.
+pub(crate) fn parse_du(output: &str) -> anyhow::Result<u64> {
+    let bytes = output
+        .split_whitespace()
+        .next()
+        .ok_or(anyhow::anyhow!("empty du output"))?;
+    Ok(bytes.parse()?)
+}
+
+/// Reads a numeric `field:value` line from Redis `INFO` output.
+pub(crate) fn parse_info_field(output: &str, field: &str) -> anyhow::Result<u64> {
+    let value = output
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))
+        .ok_or(anyhow::anyhow!("no {field} in INFO output"))?;
+    Ok(value.trim().parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_outputs() -> anyhow::Result<()> {
+        assert_eq!(parse_du("123456\t/data\n")?, 123456);
+        assert!(parse_du("").is_err());
+        let info = "# Memory\r\nused_memory:1048576\r\nused_memory_dataset:524288\r\n";
+        assert_eq!(parse_info_field(info, "used_memory")?, 1048576);
+        assert_eq!(parse_info_field(info, "used_memory_dataset")?, 524288);
+        assert!(parse_info_field(info, "used_memory_rss").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn per_row() {
+        let footprint = Footprint {
+            memory_bytes: Some(1000),
+            disk_bytes: None,
+        };
+        assert_eq!(footprint.memory_per_row(10), Some(100.0));
+        assert_eq!(footprint.memory_per_row(0), None);
+        assert_eq!(footprint.disk_per_row(10), None);
+    }
+}
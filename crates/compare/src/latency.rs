@@ -0,0 +1,127 @@
+/// Latency distribution of single operations, recorded in nanoseconds.
+#[derive(Debug, Clone)]
+pub struct Histogram(hdrhistogram::Histogram<u64>);
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    /// Precision kept over the whole range, in significant decimal digits.
+    const SIGNIFICANT_DIGITS: u8 = 3;
+    /// Longer latencies are recorded as this one
+    const MAX_LATENCY: std::time::Duration = std::time::Duration::from_secs(3600);
+
+    pub fn new() -> Self {
+        let max = Self::MAX_LATENCY.as_nanos() as u64;
+        let histogram = hdrhistogram::Histogram::new_with_max(max, Self::SIGNIFICANT_DIGITS)
+            .expect("valid histogram bounds");
+        Histogram(histogram)
+    }
+
+    pub fn record(&mut self, latency: std::time::Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.0.saturating_record(nanos.max(1));
+    }
+
+    /// Runs `future`, recording how long it took.
+    pub async fn time<T>(&mut self, future: impl Future<Output = T>) -> T {
+        let started = std::time::Instant::now();
+        let output = future.await;
+        self.record(started.elapsed());
+        output
+    }
+
+    pub fn add(&mut self, other: &Histogram) {
+        // Both sides have the same bounds, adding cannot fail
+        self.0
+            .add(&other.0)
+            .expect("histograms with the same bounds");
+    }
+
+    pub fn len(&self) -> u64 {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Reported percentiles, `None` when nothing was recorded.
+    pub fn percentiles(&self) -> Option<Percentiles> {
+        if self.is_empty() {
+            return None;
+        }
+        let at = |quantile| std::time::Duration::from_nanos(self.0.value_at_quantile(quantile));
+        Some(Percentiles {
+            p50: at(0.5),
+            p90: at(0.9),
+            p99: at(0.99),
+            p999: at(0.999),
+            max: std::time::Duration::from_nanos(self.0.max()),
+        })
+    }
+}
+
+/// Summary of a [`Histogram`] kept in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: std::time::Duration,
+    pub p90: std::time::Duration,
+    pub p99: std::time::Duration,
+    pub p999: std::time::Duration,
+    pub max: std::time::Duration,
+}
+
+impl Percentiles {
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, std::time::Duration)> {
+        [
+            ("p50", self.p50),
+            ("p90", self.p90),
+            ("p99", self.p99),
+            ("p99.9", self.p999),
+            ("max", self.max),
+        ]
+        .into_iter()
+    }
+}
+
+impl std::fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (name, latency)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{name} {latency:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_of_recorded_latencies() {
+        let mut histogram = Histogram::new();
+        assert_eq!(histogram.percentiles(), None);
+        for micros in 1..=1000 {
+            histogram.record(std::time::Duration::from_micros(micros));
+        }
+        let percentiles = histogram.percentiles().unwrap();
+        // Values are kept with 3 significant digits
+        let micros = |latency: std::time::Duration| (latency.as_nanos() as f64 / 1000.0).round();
+        assert_eq!(micros(percentiles.p50), 500.0);
+        assert_eq!(micros(percentiles.p90), 900.0);
+        assert_eq!(micros(percentiles.p99), 990.0);
+        assert_eq!(micros(percentiles.max), 1000.0);
+
+        let mut total = Histogram::new();
+        total.add(&histogram);
+        total.add(&histogram);
+        assert_eq!(total.len(), 2000);
+    }
+}
@@ -1,35 +1,374 @@
+pub mod artifacts;
 pub mod backends;
 pub mod docker;
+pub mod driver;
+pub mod footprint;
+pub mod latency;
+pub mod lifecycle;
+pub mod registry;
+pub mod report;
+pub mod runtime;
+pub mod scaling;
+pub mod storage;
+pub mod timeout;
+pub mod verify;
+pub mod workload;
 
+/// Database benchmarked through the phases of a run, see [`timeout::Phase`].
+///
+/// Every phase is a hook the lifecycle runs within the phase timeout and
+/// times on its own: [`Backend::provision`], [`Backend::ready`],
+/// [`Backend::load_fixture`] and [`Backend::warm_up`] prepare an instance,
+/// [`Bencher::run`] is the measured part, then [`Backend::verify`],
+/// [`Backend::footprint`] and [`Backend::teardown`] follow.
 pub trait Backend {
-    type Input;
-    type Bencher: Bencher<Input = Self::Input>;
+    /// Short name used in benchmark ids and reports
+    const NAME: &str;
+    /// Workloads [`Backend::provision`] accepts
+    const WORKLOADS: &[workload::Workload];
+    /// Load modes [`Backend::provision`] accepts
+    const LOAD_MODES: &[LoadMode] = &[LoadMode::Exec, LoadMode::Host, LoadMode::Driver];
+    /// Limit of [`Backend::ready`] unless one is configured, for servers
+    /// much slower to start than most
+    const READY_LIMIT: Option<std::time::Duration> = None;
 
-    fn setup(docker: bollard::Docker) -> impl Future<Output = Self> + Send;
+    /// Provisioned container carried through the phases before the measurement
+    type Instance: Send;
+    type Bencher: Bencher;
 
-    fn prepare(
+    fn setup(config: BackendConfig) -> impl Future<Output = Self> + Send;
+
+    fn config(&self) -> &BackendConfig;
+
+    /// Creates and starts a fresh container for `input.workload`, which is
+    /// one of [`Backend::WORKLOADS`].
+    fn provision(
+        &self,
+        input: &workload::WorkloadInput,
+    ) -> impl Future<Output = anyhow::Result<Self::Instance>> + Send;
+
+    /// Waits until the server accepts clients.
+    fn ready(
         &self,
-        input: &Self::Input,
-    ) -> impl Future<Output = anyhow::Result<Self::Bencher>> + Send;
+        instance: &mut Self::Instance,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send {
+        let _ = instance;
+        async { Ok(()) }
+    }
+
+    /// Uploads the dataset and sets up everything the measured load needs.
+    fn load_fixture(
+        &self,
+        instance: &mut Self::Instance,
+        input: &workload::WorkloadInput,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Brings caches to their steady state before the measurement.
+    fn warm_up(
+        &self,
+        instance: &mut Self::Instance,
+        input: &workload::WorkloadInput,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send {
+        let _ = (instance, input);
+        async { Ok(()) }
+    }
+
+    /// Hands the prepared instance over to the measured load, without any I/O.
+    fn bencher(&self, instance: Self::Instance) -> anyhow::Result<Self::Bencher>;
+
+    /// Measures the data loaded into the container held by `container_guard`.
+    fn footprint(
+        &self,
+        container_guard: &crate::docker::ContainerGuard,
+    ) -> impl Future<Output = anyhow::Result<footprint::Footprint>> + Send;
+
+    /// Checks the data loaded by the measured load against `input`, so that
+    /// a load which silently lost rows fails instead of looking fast.
+    fn verify(
+        &self,
+        input: &workload::WorkloadInput,
+        outcome: &BenchOutcome,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Removes the container of a finished run.
+    fn teardown(&self, outcome: BenchOutcome) -> impl Future<Output = anyhow::Result<()>> + Send {
+        outcome.container_guard.remove()
+    }
 }
 
 pub trait Bencher {
-    type Input;
+    fn run(self) -> impl Future<Output = anyhow::Result<BenchOutcome>> + Send;
+}
+
+/// What the load tool reported about the load it performed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoaderReport {
+    /// Rows the loader claims to have written, or operations it got
+    /// answers for in read workloads
+    pub rows_loaded: Option<u64>,
+    /// Rows or commands the server rejected
+    pub errors: u64,
+    /// Time the load took according to the server
+    pub server_elapsed: Option<std::time::Duration>,
+    /// Latency of single operations, known when the client times each one
+    pub latency: Option<latency::Percentiles>,
+}
+
+/// Result of a measured load.
+pub struct BenchOutcome {
+    pub loader: LoaderReport,
+    /// Keeps the loaded container around until the outcome is dropped
+    pub container_guard: crate::docker::ContainerGuard,
+}
+
+/// Load performed outside of the measurements by [`Context::probe`].
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub elapsed: std::time::Duration,
+    pub loader: LoaderReport,
+    pub footprint: footprint::Footprint,
+    pub phases: lifecycle::PhaseTimings,
+}
+
+/// Where the load is generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Database CLI tool executed inside the database container
+    #[default]
+    Exec,
+    /// Benchmark process connecting to a published port, so the client
+    /// does not compete with the server for the container CPU
+    Host,
+    /// Native client driver in the benchmark process issuing operations
+    /// one at a time, timing each of them
+    Driver,
+}
+
+impl LoadMode {
+    pub const LOAD_MODE_ENV: &str = "DB_TEST_LOAD_MODE";
+
+    /// Reads `DB_TEST_LOAD_MODE`, either `exec` (the default), `host` or `driver`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(Self::LOAD_MODE_ENV) {
+            Ok(load_mode) => load_mode.parse(),
+            Err(_) => Ok(LoadMode::Exec),
+        }
+    }
+
+    /// Short name used in benchmark ids.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoadMode::Exec => "exec",
+            LoadMode::Host => "host",
+            LoadMode::Driver => "driver",
+        }
+    }
+
+    /// Whether the load connects to the published client port.
+    pub fn from_host(&self) -> bool {
+        matches!(self, LoadMode::Host | LoadMode::Driver)
+    }
+}
+
+impl std::str::FromStr for LoadMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exec" => Ok(LoadMode::Exec),
+            "host" => Ok(LoadMode::Host),
+            "driver" => Ok(LoadMode::Driver),
+            _ => anyhow::bail!("invalid load mode: {s}"),
+        }
+    }
+}
+
+/// Environment the backends are set up in.
+#[derive(Debug, Clone)]
+pub struct BackendConfig {
+    pub runtime: runtime::Runtime,
+    pub storage: storage::Storage,
+    pub load_mode: LoadMode,
+    pub timeouts: timeout::Timeouts,
+    /// Per-run directory for container artifacts, nothing is collected when unset
+    pub artifacts_dir: Option<std::path::PathBuf>,
+}
+
+impl BackendConfig {
+    pub fn new(runtime: runtime::Runtime) -> Self {
+        BackendConfig {
+            runtime,
+            storage: Default::default(),
+            load_mode: Default::default(),
+            timeouts: Default::default(),
+            artifacts_dir: None,
+        }
+    }
 
-    fn run(self) -> impl Future<Output = anyhow::Result<crate::docker::ContainerGuard>> + Send;
+    /// Reads `DB_TEST_RUNTIME`, `DB_TEST_STORAGE`, `DB_TEST_LOAD_MODE`,
+    /// `DB_TEST_TIMEOUTS` and `DB_TEST_ARTIFACTS_DIR`, the latter getting a
+    /// fresh directory per run.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut config = BackendConfig::new(runtime::Runtime::from_env()?)
+            .with_storage(storage::Storage::from_env()?)
+            .with_load_mode(LoadMode::from_env()?)
+            .with_timeouts(timeout::Timeouts::from_env()?);
+        if let Some(artifacts_dir) = std::env::var_os(artifacts::ARTIFACTS_DIR_ENV) {
+            let run_dir = artifacts::run_dir(std::path::Path::new(&artifacts_dir));
+            config = config.with_artifacts_dir(run_dir);
+        }
+        Ok(config)
+    }
+
+    pub fn with_storage(mut self, storage: storage::Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn with_load_mode(mut self, load_mode: LoadMode) -> Self {
+        self.load_mode = load_mode;
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: timeout::Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub fn with_artifacts_dir(mut self, artifacts_dir: impl Into<std::path::PathBuf>) -> Self {
+        self.artifacts_dir = Some(artifacts_dir.into());
+        self
+    }
+}
+
+/// Environment a [`Context`] runs its backend in.
+#[derive(Debug, Clone)]
+pub struct ContextConfig {
+    pub backend: BackendConfig,
+    /// Worker threads of the async runtime, one per core when unset
+    pub worker_threads: Option<usize>,
 }
 
-pub struct Context<B> {
+impl ContextConfig {
+    pub const WORKER_THREADS_ENV: &str = "DB_TEST_WORKER_THREADS";
+
+    pub fn new(backend: BackendConfig) -> Self {
+        ContextConfig {
+            backend,
+            worker_threads: None,
+        }
+    }
+
+    /// Reads `DB_TEST_WORKER_THREADS` on top of [`BackendConfig::from_env`].
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut config = ContextConfig::new(BackendConfig::from_env()?);
+        if let Ok(worker_threads) = std::env::var(Self::WORKER_THREADS_ENV) {
+            let worker_threads = worker_threads.parse().map_err(|_| {
+                anyhow::anyhow!("invalid {}: {worker_threads}", Self::WORKER_THREADS_ENV)
+            })?;
+            config = config.with_worker_threads(worker_threads);
+        }
+        Ok(config)
+    }
+
+    pub fn with_worker_threads(mut self, worker_threads: usize) -> Self {
+        self.worker_threads = Some(worker_threads);
+        self
+    }
+}
+
+pub struct Context {
     pub runtime: tokio::runtime::Runtime,
-    pub backend: B,
+    pub config: BackendConfig,
+    pub backend: Box<dyn registry::DynBackend>,
 }
 
-impl<B: Backend> Context<B> {
-    pub fn new() -> anyhow::Result<Self> {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let docker = bollard::Docker::connect_with_local_defaults()?;
-        let backend = runtime.block_on(B::setup(docker));
-        Ok(Context { runtime, backend })
+impl Context {
+    pub fn new(factory: &registry::BackendFactory) -> anyhow::Result<Self> {
+        Self::with_config(factory, ContextConfig::from_env()?)
+    }
+
+    /// Fails with [`runtime::Unreachable`] when the container runtime does
+    /// not answer, which callers may treat as a reason to skip the backend.
+    pub fn with_config(
+        factory: &registry::BackendFactory,
+        config: ContextConfig,
+    ) -> anyhow::Result<Self> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        if let Some(worker_threads) = config.worker_threads {
+            anyhow::ensure!(worker_threads > 0, "worker threads must be positive");
+            builder.worker_threads(worker_threads);
+        }
+        let runtime = builder.enable_all().build()?;
+        let ContextConfig {
+            backend: config, ..
+        } = config;
+        runtime
+            .block_on(config.runtime.ping())
+            .map_err(runtime::Unreachable)?;
+        let backend = runtime.block_on(factory.setup(config.clone()));
+        Ok(Context {
+            runtime,
+            config,
+            backend,
+        })
+    }
+
+    /// Runs every phase for `input` once outside of the measurements,
+    /// recording how long each took, what the loader reported and the
+    /// footprint the load left behind.
+    ///
+    /// The loaded data is verified first.
+    pub fn probe(&self, input: &workload::WorkloadInput) -> anyhow::Result<Probe> {
+        self.block(async {
+            let timeouts = &self.config.timeouts;
+            let registry::Prepared { bench, mut phases } = self.backend.prepare(input).await?;
+            let started = std::time::Instant::now();
+            let outcome = bench.await?;
+            let elapsed = started.elapsed();
+            phases.record(timeout::Phase::Measure, elapsed);
+            let verify = self.backend.verify(input, &outcome);
+            phases
+                .time(timeouts, timeout::Phase::Verify, verify)
+                .await?;
+            let footprint = self.backend.footprint(&outcome.container_guard);
+            let footprint = phases
+                .time(timeouts, timeout::Phase::Footprint, footprint)
+                .await?;
+            let loader = outcome.loader;
+            let teardown = self.backend.teardown(outcome);
+            phases
+                .time(timeouts, timeout::Phase::Teardown, teardown)
+                .await?;
+            Ok(Probe {
+                elapsed,
+                loader,
+                footprint,
+                phases,
+            })
+        })
+    }
+
+    /// Runs [`Backend::verify`] within the [`timeout::Phase::Verify`] timeout.
+    pub async fn verify(
+        &self,
+        input: &workload::WorkloadInput,
+        outcome: &BenchOutcome,
+    ) -> anyhow::Result<()> {
+        let verify = self.backend.verify(input, outcome);
+        self.config
+            .timeouts
+            .within(timeout::Phase::Verify, verify)
+            .await
+    }
+
+    /// Runs [`Backend::teardown`] within the [`timeout::Phase::Teardown`] timeout.
+    pub async fn teardown(&self, outcome: BenchOutcome) -> anyhow::Result<()> {
+        let teardown = self.backend.teardown(outcome);
+        self.config
+            .timeouts
+            .within(timeout::Phase::Teardown, teardown)
+            .await
     }
 
     pub fn block<O>(&self, f: impl Future<Output = O>) -> O {
@@ -37,6 +376,85 @@ impl<B: Backend> Context<B> {
     }
 }
 
-pub struct InsertBulkInput {
-    pub file_path: std::path::PathBuf,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreachable_runtime_is_reported() -> anyhow::Result<()> {
+        // Nothing listens on the discard port
+        let endpoint = runtime::DockerEndpoint::Http("http://127.0.0.1:9".into());
+        let connection = runtime::DockerConnection::new(endpoint)
+            .with_timeout(std::time::Duration::from_secs(5))
+            .with_api_version(1, 41);
+        let runtime = runtime::Runtime::new(runtime::DockerRuntime::connect(&connection)?);
+        let config = ContextConfig::new(BackendConfig::new(runtime)).with_worker_threads(1);
+        let factory = registry::select("redis")?[0];
+        let err = Context::with_config(factory, config).err().unwrap();
+        assert!(err.is::<runtime::Unreachable>(), "{err:#}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::runtime::{ExecOutput, FakeRuntime, fake::Call};
+
+    /// Output of an exec which only printed `stdout`.
+    pub fn stdout(stdout: impl Into<String>) -> ExecOutput {
+        ExecOutput {
+            stdout: stdout.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Fake runtime whose server is ready right away, printing `ready` from
+    /// the readiness checks containing `pattern`.
+    pub fn ready_fake(pattern: &str, ready: &str) -> FakeRuntime {
+        let fake = FakeRuntime::new();
+        fake.script_exec(pattern, stdout(ready));
+        fake
+    }
+
+    /// Command lines of the execs created so far, in order.
+    pub fn execs(fake: &FakeRuntime) -> Vec<String> {
+        fake.calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                Call::CreateExec { cmd, .. } => Some(cmd.join(" ")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Scripts the statements of [`verify::sql_rows`](crate::verify::sql_rows)
+    /// to read back every row of `input`, with `separator` between the counts.
+    pub fn script_sql_rows(
+        fake: &FakeRuntime,
+        input: &crate::workload::WorkloadInput,
+        separator: char,
+    ) -> anyhow::Result<db_test_model::DataSample> {
+        let expected = db_test_model::sample_data_file(&input.file_path, 10)?;
+        let counts = format!("{}{separator}{}\n", input.expected_rows()?, expected.users);
+        fake.script_exec("count(*)", stdout(counts));
+        for row in &expected.sample {
+            let lookup = format!("trans_hash = '{}'", row.transaction_id);
+            fake.script_exec(&lookup, stdout(format!("{}\n", row.timestamp)));
+        }
+        Ok(expected)
+    }
+
+    /// Writes `rows` generated transactions into a fresh directory named after the test.
+    pub fn gen_test_csv(test_name: &str, rows: usize) -> anyhow::Result<std::path::PathBuf> {
+        let csv_file_dir = std::env::temp_dir().join("db-test-compare").join(test_name);
+        let _ = std::fs::remove_dir_all(&csv_file_dir);
+        std::fs::create_dir_all(&csv_file_dir)?;
+        let csv_file_path = csv_file_dir.join("data.csv");
+        let mut csv_writer = csv::Writer::from_path(&csv_file_path)?;
+        let transactions = db_test_model::bulk_data::BulkDataGenerator::new();
+        for transaction in transactions.take(rows) {
+            transaction.serialize_csv(&mut csv_writer)?;
+        }
+        Ok(csv_file_path)
+    }
 }
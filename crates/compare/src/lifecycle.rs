@@ -0,0 +1,79 @@
+use crate::timeout::{Phase, Timeouts};
+
+/// How long each phase of one run took.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseTimings(std::collections::BTreeMap<Phase, std::time::Duration>);
+
+impl PhaseTimings {
+    /// Adds `elapsed` to the time spent in `phase`.
+    pub fn record(&mut self, phase: Phase, elapsed: std::time::Duration) {
+        *self.0.entry(phase).or_default() += elapsed;
+    }
+
+    pub fn get(&self, phase: Phase) -> Option<std::time::Duration> {
+        self.0.get(&phase).copied()
+    }
+
+    /// Recorded phases in the order they run.
+    pub fn iter(&self) -> impl Iterator<Item = (Phase, std::time::Duration)> + '_ {
+        self.0.iter().map(|(phase, elapsed)| (*phase, *elapsed))
+    }
+
+    pub fn extend(&mut self, other: &PhaseTimings) {
+        for (phase, elapsed) in other.iter() {
+            self.record(phase, elapsed);
+        }
+    }
+
+    /// Runs `future` as `phase` within its timeout, recording how long it
+    /// took, failed attempts included.
+    pub async fn time<T>(
+        &mut self,
+        timeouts: &Timeouts,
+        phase: Phase,
+        future: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let started = std::time::Instant::now();
+        let output = timeouts.within(phase, future).await;
+        self.record(phase, started.elapsed());
+        output
+    }
+}
+
+impl std::fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (phase, elapsed)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{phase} {elapsed:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn phases_are_timed_in_order() -> anyhow::Result<()> {
+        let timeouts = Timeouts::default().with_phase(Phase::Ready, std::time::Duration::ZERO);
+        let mut timings = PhaseTimings::default();
+        timings
+            .time(&timeouts, Phase::Verify, async { Ok(()) })
+            .await?;
+        timings
+            .time(&timeouts, Phase::Provision, async { Ok(()) })
+            .await?;
+        let ready = timings.time(
+            &timeouts,
+            Phase::Ready,
+            std::future::pending::<anyhow::Result<()>>(),
+        );
+        assert!(ready.await.is_err());
+        let phases = timings.iter().map(|(phase, _)| phase).collect::<Vec<_>>();
+        assert_eq!(phases, vec![Phase::Provision, Phase::Ready, Phase::Verify]);
+        Ok(())
+    }
+}
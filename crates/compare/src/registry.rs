@@ -0,0 +1,321 @@
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+
+use crate::backends::{clickhouse, mongo, mysql, postgres, redis, scylla, sqlite};
+use crate::docker::ContainerGuard;
+use crate::footprint::Footprint;
+use crate::lifecycle::PhaseTimings;
+use crate::timeout::Phase;
+use crate::workload::{Workload, WorkloadInput};
+use crate::{Backend, BackendConfig, BenchOutcome, Bencher, LoadMode};
+
+/// Prepared load, measured from its first poll.
+pub type DynBench = BoxFuture<'static, anyhow::Result<BenchOutcome>>;
+
+/// Instance brought through every phase before the measurement.
+pub struct Prepared {
+    pub bench: DynBench,
+    /// Time spent in the phases up to the measurement
+    pub phases: PhaseTimings,
+}
+
+/// Object safe counterpart of [`Backend`], implemented for every backend.
+pub trait DynBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn workloads(&self) -> &'static [Workload];
+
+    fn load_modes(&self) -> &'static [LoadMode];
+
+    /// Runs the phases up to the measurement, each within its timeout.
+    fn prepare<'a>(&'a self, input: &'a WorkloadInput) -> BoxFuture<'a, anyhow::Result<Prepared>>;
+
+    fn footprint<'a>(
+        &'a self,
+        container_guard: &'a ContainerGuard,
+    ) -> BoxFuture<'a, anyhow::Result<Footprint>>;
+
+    fn verify<'a>(
+        &'a self,
+        input: &'a WorkloadInput,
+        outcome: &'a BenchOutcome,
+    ) -> BoxFuture<'a, anyhow::Result<()>>;
+
+    fn teardown(&self, outcome: BenchOutcome) -> BoxFuture<'_, anyhow::Result<()>>;
+}
+
+impl<B> DynBackend for B
+where
+    B: Backend + Send + Sync + 'static,
+    B::Bencher: Send + 'static,
+{
+    fn name(&self) -> &'static str {
+        B::NAME
+    }
+
+    fn workloads(&self) -> &'static [Workload] {
+        B::WORKLOADS
+    }
+
+    fn load_modes(&self) -> &'static [LoadMode] {
+        B::LOAD_MODES
+    }
+
+    fn prepare<'a>(&'a self, input: &'a WorkloadInput) -> BoxFuture<'a, anyhow::Result<Prepared>> {
+        async move {
+            let mut timeouts = self.config().timeouts.clone();
+            if let Some(limit) = B::READY_LIMIT {
+                timeouts = timeouts.with_default_phase(Phase::Ready, limit);
+            }
+            let timeouts = &timeouts;
+            let mut phases = PhaseTimings::default();
+            let provision = self.provision(input);
+            let mut instance = phases.time(timeouts, Phase::Provision, provision).await?;
+            let ready = self.ready(&mut instance);
+            phases.time(timeouts, Phase::Ready, ready).await?;
+            let load_fixture = self.load_fixture(&mut instance, input);
+            phases
+                .time(timeouts, Phase::LoadFixture, load_fixture)
+                .await?;
+            let warm_up = self.warm_up(&mut instance, input);
+            phases.time(timeouts, Phase::WarmUp, warm_up).await?;
+            let bench = self.bencher(instance)?.run().boxed();
+            Ok(Prepared { bench, phases })
+        }
+        .boxed()
+    }
+
+    fn footprint<'a>(
+        &'a self,
+        container_guard: &'a ContainerGuard,
+    ) -> BoxFuture<'a, anyhow::Result<Footprint>> {
+        Backend::footprint(self, container_guard).boxed()
+    }
+
+    fn verify<'a>(
+        &'a self,
+        input: &'a WorkloadInput,
+        outcome: &'a BenchOutcome,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Backend::verify(self, input, outcome).boxed()
+    }
+
+    fn teardown(&self, outcome: BenchOutcome) -> BoxFuture<'_, anyhow::Result<()>> {
+        Backend::teardown(self, outcome).boxed()
+    }
+}
+
+/// Sets up a backend known by its name only.
+#[derive(Clone, Copy)]
+pub struct BackendFactory {
+    pub name: &'static str,
+    setup: fn(BackendConfig) -> BoxFuture<'static, Box<dyn DynBackend>>,
+}
+
+impl BackendFactory {
+    pub const fn of<B>() -> Self
+    where
+        B: Backend + Send + Sync + 'static,
+        B::Bencher: Send + 'static,
+    {
+        BackendFactory {
+            name: B::NAME,
+            setup: Self::setup_boxed::<B>,
+        }
+    }
+
+    fn setup_boxed<B>(config: BackendConfig) -> BoxFuture<'static, Box<dyn DynBackend>>
+    where
+        B: Backend + Send + Sync + 'static,
+        B::Bencher: Send + 'static,
+    {
+        async move { Box::new(B::setup(config).await) as Box<dyn DynBackend> }.boxed()
+    }
+
+    pub fn setup(&self, config: BackendConfig) -> BoxFuture<'static, Box<dyn DynBackend>> {
+        (self.setup)(config)
+    }
+}
+
+impl std::fmt::Debug for BackendFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BackendFactory").field(&self.name).finish()
+    }
+}
+
+/// Every backend a run can select.
+pub static BACKENDS: &[BackendFactory] =
+    &concat::<{ FIXED.len() + sqlite::MATRIX.len() }>(FIXED, sqlite::MATRIX);
+
+/// Backends registered one by one, before the generated variants.
+const FIXED: &[BackendFactory] = &[
+    BackendFactory::of::<redis::Redis>(),
+    BackendFactory::of::<redis::Redis<redis::NoPersistence>>(),
+    BackendFactory::of::<redis::Redis<redis::RdbSnapshots>>(),
+    BackendFactory::of::<redis::Redis<redis::AofEverySec>>(),
+    BackendFactory::of::<redis::Redis<redis::AofAlways>>(),
+    BackendFactory::of::<redis::Redis<redis::Valkey>>(),
+    BackendFactory::of::<redis::Redis<redis::KeyDb>>(),
+    BackendFactory::of::<redis::Redis<redis::Dragonfly>>(),
+    BackendFactory::of::<postgres::Postgres>(),
+    BackendFactory::of::<postgres::Postgres<postgres::CopyText>>(),
+    BackendFactory::of::<postgres::Postgres<postgres::CopyBinary>>(),
+    BackendFactory::of::<postgres::Postgres<postgres::ValuesBatches>>(),
+    BackendFactory::of::<postgres::Postgres<postgres::PreparedBatches>>(),
+    BackendFactory::of::<postgres::Postgres<postgres::UnnestBatches>>(),
+    BackendFactory::of::<sqlite::Sqlite>(),
+    BackendFactory::of::<mysql::Mysql>(),
+    BackendFactory::of::<mysql::Mysql<mysql::ValuesBatches>>(),
+    BackendFactory::of::<mysql::Mysql<mysql::MariaDbLoadData>>(),
+    BackendFactory::of::<mysql::Mysql<mysql::MariaDbValuesBatches>>(),
+    BackendFactory::of::<mongo::Mongo>(),
+    BackendFactory::of::<mongo::Mongo<mongo::IndexAfterLoad>>(),
+    BackendFactory::of::<mongo::Mongo<mongo::Unacknowledged>>(),
+    BackendFactory::of::<mongo::Mongo<mongo::Journaled>>(),
+    BackendFactory::of::<mongo::Mongo<mongo::InsertMany>>(),
+    BackendFactory::of::<clickhouse::ClickHouse>(),
+    BackendFactory::of::<scylla::Scylla>(),
+    BackendFactory::of::<scylla::Scylla<scylla::Cassandra>>(),
+];
+
+const fn concat<const N: usize>(
+    first: &[BackendFactory],
+    second: &[BackendFactory],
+) -> [BackendFactory; N] {
+    assert!(first.len() + second.len() == N);
+    let mut all = [first[0]; N];
+    let mut i = 0;
+    while i < N {
+        all[i] = if i < first.len() {
+            first[i]
+        } else {
+            second[i - first.len()]
+        };
+        i += 1;
+    }
+    all
+}
+
+/// Comma separated backend filter, every backend is selected when unset.
+pub const BACKENDS_ENV: &str = "DB_TEST_BACKENDS";
+
+/// Backends selected by `DB_TEST_BACKENDS`, see [`select`].
+pub fn from_env() -> anyhow::Result<Vec<&'static BackendFactory>> {
+    match std::env::var(BACKENDS_ENV) {
+        Ok(filter) => select(&filter),
+        Err(_) => select(""),
+    }
+}
+
+/// Backends matching `filter`, in registration order.
+///
+/// The filter is a comma separated list of backend names, where a trailing
+/// `*` matches any suffix, e.g. `redis,postgres-*`. An empty filter selects
+/// every backend, a pattern matching nothing is an error.
+pub fn select(filter: &str) -> anyhow::Result<Vec<&'static BackendFactory>> {
+    let patterns = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .collect::<Vec<_>>();
+    if patterns.is_empty() {
+        return Ok(BACKENDS.iter().collect());
+    }
+    let matches = |pattern: &str, name: &str| match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    };
+    for pattern in &patterns {
+        if !BACKENDS
+            .iter()
+            .any(|backend| matches(pattern, backend.name))
+        {
+            let names = BACKENDS.iter().map(|backend| backend.name);
+            anyhow::bail!(
+                "no backend matches {pattern}, known backends: {}",
+                names.collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+    let selected = BACKENDS
+        .iter()
+        .filter(|backend| {
+            patterns
+                .iter()
+                .any(|pattern| matches(pattern, backend.name))
+        })
+        .collect();
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ExecOutput, Runtime};
+
+    #[test]
+    fn select_by_name() -> anyhow::Result<()> {
+        let names = |filter| -> anyhow::Result<Vec<&str>> {
+            Ok(select(filter)?.iter().map(|backend| backend.name).collect())
+        };
+        assert_eq!(names("")?.len(), BACKENDS.len());
+        assert_eq!(names("postgres")?, vec!["postgres"]);
+        assert_eq!(
+            names("postgres-copy-*")?,
+            vec!["postgres-copy-text", "postgres-copy-binary"]
+        );
+        assert_eq!(names("redis")?, vec!["redis"]);
+        assert_eq!(names("sqlite")?, vec!["sqlite"]);
+        assert_eq!(names("mariadb*")?, vec!["mariadb", "mariadb-insert-values"]);
+        let matrix = names("sqlite-*")?;
+        assert_eq!(matrix.len(), 4 * 3 * 2 * 2 * 2);
+        assert_eq!(matrix[0], "sqlite-delete-off-p4096-c2048k-b1000");
+        assert_eq!(names("sqlite-wal-normal-*")?.len(), 8);
+        let unique = BACKENDS.iter().map(|backend| backend.name);
+        assert_eq!(
+            unique.collect::<std::collections::HashSet<_>>().len(),
+            BACKENDS.len()
+        );
+        assert_eq!(
+            names("redis-aof-*, redis")?,
+            vec!["redis", "redis-aof-everysec", "redis-aof-always"]
+        );
+        assert!(select("redis,etcd").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn factory_sets_up_erased_backend() -> anyhow::Result<()> {
+        let fake = crate::testing::ready_fake("redis-cli PING", "PONG\n");
+        fake.script_exec(
+            "redis-cli --pipe",
+            ExecOutput {
+                stdout: "errors: 0, replies: 10\n".into(),
+                ..Default::default()
+            },
+        );
+        let factory = select("redis")?[0];
+        let backend = factory
+            .setup(BackendConfig::new(Runtime::new(fake.clone())))
+            .await;
+        assert_eq!(backend.name(), "redis");
+        let file_path =
+            crate::testing::gen_test_csv("registry_factory_sets_up_erased_backend", 10)?;
+        let bench_input = WorkloadInput::new(Workload::BulkInsert, file_path);
+        let prepared = backend.prepare(&bench_input).await?;
+        let phases = prepared.phases.iter().map(|(phase, _)| phase);
+        assert_eq!(
+            phases.collect::<Vec<_>>(),
+            vec![
+                Phase::Provision,
+                Phase::Ready,
+                Phase::LoadFixture,
+                Phase::WarmUp
+            ]
+        );
+        let outcome = prepared.bench.await?;
+        assert_eq!(outcome.loader.rows_loaded, Some(10));
+        backend.teardown(outcome).await?;
+        Ok(())
+    }
+}
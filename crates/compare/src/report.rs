@@ -0,0 +1,182 @@
+use crate::LoaderReport;
+use crate::footprint::Footprint;
+use crate::lifecycle::PhaseTimings;
+use crate::scaling::ScalingCurve;
+use crate::timeout::Phase;
+
+/// CSV file the load reports are appended to, they are only printed when unset.
+pub const REPORT_FILE_ENV: &str = "DB_TEST_REPORT_FILE";
+
+/// Outcome of loading one dataset, kept next to the criterion timings.
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    pub benchmark: String,
+    pub dataset: String,
+    /// Rows stored once the workload is done, the footprint is divided by them
+    pub rows: u64,
+    pub elapsed: std::time::Duration,
+    pub loader: LoaderReport,
+    pub footprint: Footprint,
+    /// Time spent in every phase of the probed run
+    pub phases: PhaseTimings,
+}
+
+impl LoadReport {
+    const CSV_HEADER: &str = "benchmark,dataset,rows,elapsed_ns,rows_loaded,loader_errors,server_elapsed_ns,\
+         memory_bytes,disk_bytes,memory_per_row,disk_per_row,\
+         p50_ns,p90_ns,p99_ns,p999_ns,max_ns,\
+         provision_ns,ready_ns,load_fixture_ns,warm_up_ns,measure_ns,verify_ns,footprint_ns,teardown_ns";
+
+    fn csv_record(&self) -> String {
+        let field = |value: Option<String>| value.unwrap_or_default();
+        let per_row = |value: Option<f64>| field(value.map(|value| format!("{value:.2}")));
+        let latency = match self.loader.latency {
+            Some(percentiles) => percentiles
+                .iter()
+                .map(|(_, latency)| latency.as_nanos().to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            None => ",,,,".into(),
+        };
+        let phases = Phase::ALL
+            .iter()
+            .map(|phase| {
+                field(
+                    self.phases
+                        .get(*phase)
+                        .map(|elapsed| elapsed.as_nanos().to_string()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.benchmark,
+            self.dataset,
+            self.rows,
+            self.elapsed.as_nanos(),
+            field(self.loader.rows_loaded.map(|rows| rows.to_string())),
+            self.loader.errors,
+            field(
+                self.loader
+                    .server_elapsed
+                    .map(|elapsed| elapsed.as_nanos().to_string())
+            ),
+            field(self.footprint.memory_bytes.map(|bytes| bytes.to_string())),
+            field(self.footprint.disk_bytes.map(|bytes| bytes.to_string())),
+            per_row(self.footprint.memory_per_row(self.rows)),
+            per_row(self.footprint.disk_per_row(self.rows)),
+            latency,
+            phases,
+        )
+    }
+
+    /// Prints the report and appends it to `DB_TEST_REPORT_FILE` if set.
+    pub fn publish(&self) -> anyhow::Result<()> {
+        println!("{self}");
+        match std::env::var_os(REPORT_FILE_ENV) {
+            Some(report_file) => self.append_to(std::path::Path::new(&report_file)),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the report as a CSV record, writing the header into new files.
+    pub fn append_to(&self, report_file: &std::path::Path) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        if let Some(report_dir) = report_file.parent() {
+            std::fs::create_dir_all(report_dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(report_file)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", Self::CSV_HEADER)?;
+        }
+        writeln!(file, "{}", self.csv_record())?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} rows in {:?}",
+            self.benchmark, self.dataset, self.rows, self.elapsed
+        )?;
+        if let Some(rows_loaded) = self.loader.rows_loaded
+            && rows_loaded != self.rows
+        {
+            write!(f, ", {rows_loaded} loaded")?;
+        }
+        if self.loader.errors > 0 {
+            write!(f, ", {} errors", self.loader.errors)?;
+        }
+        if let Some(memory_per_row) = self.footprint.memory_per_row(self.rows) {
+            write!(f, ", memory {memory_per_row:.2} B/row")?;
+        }
+        if let Some(disk_per_row) = self.footprint.disk_per_row(self.rows) {
+            write!(f, ", disk {disk_per_row:.2} B/row")?;
+        }
+        if let Some(latency) = self.loader.latency {
+            write!(f, ", latency {latency}")?;
+        }
+        if self.phases.iter().next().is_some() {
+            write!(f, ", phases {}", self.phases)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints how the throughput of `curve` grows with its clients.
+///
+/// Every point is already published as the [`LoadReport`] of its client count,
+/// so nothing is appended to `DB_TEST_REPORT_FILE`.
+pub fn publish_curve(curve: &ScalingCurve) {
+    println!("{curve}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_writes_header_once() -> anyhow::Result<()> {
+        let report_file =
+            std::env::temp_dir().join("db-test-compare/append_writes_header_once.csv");
+        let _ = std::fs::remove_file(&report_file);
+        let mut report = LoadReport {
+            benchmark: "insert_bulk/redis".into(),
+            dataset: "data_500.csv".into(),
+            rows: 500,
+            elapsed: std::time::Duration::from_millis(2),
+            loader: LoaderReport {
+                rows_loaded: Some(500),
+                ..Default::default()
+            },
+            footprint: Footprint {
+                memory_bytes: Some(50_000),
+                disk_bytes: None,
+            },
+            phases: Default::default(),
+        };
+        report
+            .phases
+            .record(Phase::Provision, std::time::Duration::from_millis(5));
+        report.append_to(&report_file)?;
+        report.append_to(&report_file)?;
+        let record =
+            "insert_bulk/redis,data_500.csv,500,2000000,500,0,,50000,,100.00,,,,,,,5000000,,,,,,,";
+        assert_eq!(
+            std::fs::read_to_string(&report_file)?,
+            format!("{}\n{record}\n{record}\n", LoadReport::CSV_HEADER)
+        );
+        assert_eq!(
+            report.to_string(),
+            "insert_bulk/redis data_500.csv: 500 rows in 2ms, memory 100.00 B/row, phases provision 5ms"
+        );
+        Ok(())
+    }
+}
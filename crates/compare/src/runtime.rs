@@ -0,0 +1,278 @@
+pub mod docker;
+pub mod fake;
+pub mod local;
+
+pub use docker::{DockerConnection, DockerEndpoint, DockerRuntime};
+pub use fake::FakeRuntime;
+pub use local::{LocalProcessRuntime, LocalProgram};
+
+/// Boxed future returned by [`ContainerRuntime`] operations.
+///
+/// The runtime is used behind `dyn`, so it cannot return `impl Future`.
+pub type RuntimeFuture<'a, T> = futures_util::future::BoxFuture<'a, anyhow::Result<T>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContainerId(pub(crate) Box<str>);
+
+impl ContainerId {
+    pub fn new(id: impl Into<Box<str>>) -> Self {
+        ContainerId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecId(pub(crate) Box<str>);
+
+impl ExecId {
+    pub fn new(id: impl Into<Box<str>>) -> Self {
+        ExecId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Filesystem mounted into a container at `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mount {
+    Tmpfs {
+        target: Box<str>,
+        size_bytes: Option<u64>,
+    },
+    /// Named volume, removed together with the container
+    Volume { target: Box<str>, name: Box<str> },
+    Bind {
+        target: Box<str>,
+        host_path: std::path::PathBuf,
+    },
+}
+
+impl Mount {
+    pub fn target(&self) -> &str {
+        match self {
+            Mount::Tmpfs { target, .. } => target,
+            Mount::Volume { target, .. } => target,
+            Mount::Bind { target, .. } => target,
+        }
+    }
+}
+
+/// Everything a runtime needs to know to create a container.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerSpec {
+    pub image: Box<str>,
+    /// `KEY=value` pairs
+    pub env: Vec<Box<str>>,
+    /// Overrides the image command when not empty
+    pub cmd: Vec<Box<str>>,
+    pub mounts: Vec<Mount>,
+    /// TCP ports published on a host port chosen by the runtime
+    pub ports: Vec<u16>,
+    /// Overrides the image user, for the container and its execs
+    pub user: Option<Box<str>>,
+}
+
+impl ContainerSpec {
+    pub fn new(image: &str) -> Self {
+        ContainerSpec {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn env(mut self, var: &str) -> Self {
+        self.env.push(var.into());
+        self
+    }
+
+    pub fn cmd<'a>(mut self, cmd: impl IntoIterator<Item = &'a str>) -> Self {
+        self.cmd = cmd.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn mount(mut self, mount: Mount) -> Self {
+        self.mounts.push(mount);
+        self
+    }
+
+    pub fn publish(mut self, port: u16) -> Self {
+        self.ports.push(port);
+        self
+    }
+
+    pub fn user(mut self, user: &str) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+}
+
+/// Output collected from a finished exec.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit status of the command, `None` if the runtime could not tell it
+    pub exit_code: Option<i64>,
+}
+
+impl ExecOutput {
+    /// Fails unless the command exited with status 0.
+    ///
+    /// An unknown status passes, the output is all there is to check then.
+    pub fn ensure_success(&self) -> anyhow::Result<()> {
+        match self.exit_code {
+            None | Some(0) => Ok(()),
+            Some(code) => Err(anyhow::anyhow!(
+                "command exited with status {code}: {}",
+                self.stderr.trim()
+            )),
+        }
+    }
+}
+
+/// Low level container operations used by the backends.
+///
+/// Implementations exist for the Docker API (which also covers Podman and
+/// any other Docker-compatible socket) and for plain local processes.
+pub trait ContainerRuntime: Send + Sync + 'static {
+    /// Checks that the runtime can be used at all, runtimes without a daemon always can.
+    fn ping(&self) -> RuntimeFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn create_container<'a>(
+        &'a self,
+        container_name: &'a str,
+        spec: &'a ContainerSpec,
+    ) -> RuntimeFuture<'a, ContainerId>;
+
+    fn start_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()>;
+
+    /// Prepares a command without running it, so that it can be started
+    /// later inside the measured section.
+    fn create_exec<'a>(
+        &'a self,
+        container_name: &'a str,
+        cmd: Vec<&'a str>,
+    ) -> RuntimeFuture<'a, ExecId>;
+
+    /// Runs a prepared command and waits until it exits.
+    fn start_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ExecOutput>;
+
+    /// Stops an exec whose [`ContainerRuntime::start_exec`] was abandoned,
+    /// e.g. on timeout. Does nothing if it has already exited.
+    fn kill_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ()>;
+
+    /// Unpacks the tar archive at `archive_path` into `dest_path` inside the container.
+    fn upload_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        archive_path: std::path::PathBuf,
+        dest_path: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()>;
+
+    /// Copies `path` out of the container into `dest_dir`, keeping its file name.
+    fn download_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        path: &'a std::path::Path,
+        dest_dir: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()>;
+
+    /// Output of the container main process so far.
+    fn logs<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, String>;
+
+    /// Host address a published container port is reachable at.
+    fn host_port<'a>(
+        &'a self,
+        container_name: &'a str,
+        port: u16,
+    ) -> RuntimeFuture<'a, std::net::SocketAddr>;
+
+    /// Removes the container together with the volumes created for it.
+    fn remove_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()>;
+
+    /// Translates a path inside the container to the path exec commands
+    /// should use. Only runtimes without filesystem isolation need this.
+    fn container_path(&self, container_name: &str, path: &std::path::Path) -> std::path::PathBuf {
+        let _ = container_name;
+        path.to_path_buf()
+    }
+}
+
+/// Shared handle to a [`ContainerRuntime`].
+#[derive(Clone)]
+pub struct Runtime(std::sync::Arc<dyn ContainerRuntime>);
+
+impl Runtime {
+    pub const RUNTIME_ENV: &str = "DB_TEST_RUNTIME";
+
+    pub fn new(runtime: impl ContainerRuntime) -> Self {
+        Runtime(std::sync::Arc::new(runtime))
+    }
+
+    /// Selects the runtime from `DB_TEST_RUNTIME`.
+    ///
+    /// Accepted values are `docker` (the default), `podman`, `local`,
+    /// `socket:<path>` for any other Docker-compatible socket and
+    /// `http:<addr>` for a Docker API served over plain HTTP, e.g.
+    /// `http:localhost:2375`. Docker connections are further configured by
+    /// [`DockerConnection::from_env`].
+    pub fn from_env() -> anyhow::Result<Self> {
+        let selector = std::env::var(Self::RUNTIME_ENV).unwrap_or_default();
+        let endpoint = match selector.as_str() {
+            "" | "docker" => DockerEndpoint::LocalDefaults,
+            "podman" => DockerRuntime::podman_endpoint(),
+            "local" => return Ok(Runtime::new(LocalProcessRuntime::new())),
+            selector => {
+                if let Some(socket_path) = selector.strip_prefix("socket:") {
+                    DockerEndpoint::Socket(socket_path.into())
+                } else if let Some(addr) = selector.strip_prefix("http:") {
+                    DockerEndpoint::Http(format!("http://{addr}"))
+                } else {
+                    anyhow::bail!("unknown {}: {selector}", Self::RUNTIME_ENV)
+                }
+            }
+        };
+        let connection = DockerConnection::from_env(endpoint)?;
+        Ok(Runtime::new(DockerRuntime::connect(&connection)?))
+    }
+}
+
+impl std::ops::Deref for Runtime {
+    type Target = dyn ContainerRuntime;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl std::fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Runtime").finish_non_exhaustive()
+    }
+}
+
+/// Error returned when the container runtime cannot be reached at all, so
+/// the benchmarks needing it can be skipped rather than failed.
+#[derive(Debug)]
+pub struct Unreachable(pub anyhow::Error);
+
+impl std::fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "container runtime is unreachable: {:#}", self.0)
+    }
+}
+
+impl std::error::Error for Unreachable {}
+
+/// Strips registry and tag from an image reference: `docker.io/library/redis:7` -> `redis`.
+pub(crate) fn image_base_name(image: &str) -> &str {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    name.split([':', '@']).next().unwrap_or(name)
+}
@@ -0,0 +1,479 @@
+use futures_util::{FutureExt, StreamExt, TryFutureExt};
+use tokio_util::io::ReaderStream;
+
+use super::{ContainerId, ContainerSpec, ExecId, ExecOutput, Mount, RuntimeFuture};
+
+/// Named volumes by the container they were created for
+type Volumes = std::collections::HashMap<Box<str>, Vec<Box<str>>>;
+
+/// Runtime talking to a Docker-compatible API.
+///
+/// Podman exposes the same API on its own socket, so it is covered by
+/// [`DockerRuntime::podman`] or [`DockerRuntime::connect_with_socket`].
+#[derive(Debug, Clone)]
+pub struct DockerRuntime {
+    docker: bollard::Docker,
+    volumes: std::sync::Arc<std::sync::Mutex<Volumes>>,
+}
+
+/// Where a [`DockerRuntime`] reaches the API.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DockerEndpoint {
+    /// `DOCKER_HOST` or the default socket of the platform
+    #[default]
+    LocalDefaults,
+    /// Unix socket (or named pipe on windows)
+    Socket(std::path::PathBuf),
+    /// Plain HTTP address, e.g. `http://localhost:2375`
+    Http(String),
+}
+
+/// Options to connect a [`DockerRuntime`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerConnection {
+    pub endpoint: DockerEndpoint,
+    /// Limit of every API request
+    pub timeout: std::time::Duration,
+    /// `major.minor` API version, the client default when unset
+    pub api_version: Option<(usize, usize)>,
+}
+
+impl Default for DockerConnection {
+    fn default() -> Self {
+        DockerConnection {
+            endpoint: Default::default(),
+            timeout: DockerRuntime::DEFAULT_TIMEOUT,
+            api_version: None,
+        }
+    }
+}
+
+impl DockerConnection {
+    pub const DOCKER_TIMEOUT_ENV: &str = "DB_TEST_DOCKER_TIMEOUT";
+    pub const DOCKER_API_VERSION_ENV: &str = "DB_TEST_DOCKER_API_VERSION";
+
+    pub fn new(endpoint: DockerEndpoint) -> Self {
+        DockerConnection {
+            endpoint,
+            ..Default::default()
+        }
+    }
+
+    /// Connection to `endpoint` with the timeout in seconds from
+    /// `DB_TEST_DOCKER_TIMEOUT` and the API version from `DB_TEST_DOCKER_API_VERSION`.
+    pub fn from_env(endpoint: DockerEndpoint) -> anyhow::Result<Self> {
+        let mut connection = DockerConnection::new(endpoint);
+        if let Ok(timeout) = std::env::var(Self::DOCKER_TIMEOUT_ENV) {
+            let timeout = timeout
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid {}: {timeout}", Self::DOCKER_TIMEOUT_ENV))?;
+            connection = connection.with_timeout(std::time::Duration::from_secs(timeout));
+        }
+        if let Ok(api_version) = std::env::var(Self::DOCKER_API_VERSION_ENV) {
+            let (major, minor) = api_version
+                .split_once('.')
+                .and_then(|(major, minor)| Some((major.parse().ok()?, minor.parse().ok()?)))
+                .ok_or(anyhow::anyhow!(
+                    "invalid {}: {api_version}",
+                    Self::DOCKER_API_VERSION_ENV
+                ))?;
+            connection = connection.with_api_version(major, minor);
+        }
+        Ok(connection)
+    }
+
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_api_version(mut self, major: usize, minor: usize) -> Self {
+        self.api_version = Some((major, minor));
+        self
+    }
+}
+
+impl DockerRuntime {
+    const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+    const PUBLISH_HOST_IP: &str = "127.0.0.1";
+    #[cfg(unix)]
+    const LOCAL_SOCKET: &str = "unix:///var/run/docker.sock";
+    #[cfg(windows)]
+    const LOCAL_SOCKET: &str = "npipe:////./pipe/docker_engine";
+    #[cfg(unix)]
+    const LOCAL_HOST_SCHEME: &str = "unix://";
+    #[cfg(windows)]
+    const LOCAL_HOST_SCHEME: &str = "npipe://";
+    const PODMAN_HOST_ENV: &str = "CONTAINER_HOST";
+    const PODMAN_ROOTFUL_SOCKET: &str = "/run/podman/podman.sock";
+
+    pub fn new(docker: bollard::Docker) -> Self {
+        DockerRuntime {
+            docker,
+            volumes: Default::default(),
+        }
+    }
+
+    /// Sets up the client only, whether the daemon is reachable is first
+    /// known from [`ContainerRuntime::ping`](super::ContainerRuntime::ping).
+    pub fn connect(connection: &DockerConnection) -> anyhow::Result<Self> {
+        let api_version = match connection.api_version {
+            Some((major_version, minor_version)) => &bollard::ClientVersion {
+                major_version,
+                minor_version,
+            },
+            None => bollard::API_DEFAULT_VERSION,
+        };
+        let timeout = connection.timeout.as_secs();
+        let docker = match &connection.endpoint {
+            DockerEndpoint::LocalDefaults => {
+                // Same lookup as `connect_with_local_defaults`, which always
+                // uses the client default API version
+                let socket_path = std::env::var("DOCKER_HOST")
+                    .ok()
+                    .filter(|host| host.starts_with(Self::LOCAL_HOST_SCHEME));
+                let socket_path = socket_path.as_deref().unwrap_or(Self::LOCAL_SOCKET);
+                bollard::Docker::connect_with_local(socket_path, timeout, api_version)?
+            }
+            DockerEndpoint::Socket(socket_path) => {
+                let socket_path = socket_path.to_str().ok_or(anyhow::anyhow!(
+                    "invalid socket path: {}",
+                    socket_path.display()
+                ))?;
+                bollard::Docker::connect_with_socket(socket_path, timeout, api_version)?
+            }
+            DockerEndpoint::Http(addr) => {
+                bollard::Docker::connect_with_http(addr, timeout, api_version)?
+            }
+        };
+        Ok(DockerRuntime::new(docker))
+    }
+
+    /// Connects to the local Docker daemon, honoring `DOCKER_HOST`.
+    pub fn connect_with_local_defaults() -> anyhow::Result<Self> {
+        Self::connect(&DockerConnection::default())
+    }
+
+    /// Connects to any Docker-compatible unix socket (or named pipe on windows).
+    pub fn connect_with_socket(socket_path: &str) -> anyhow::Result<Self> {
+        Self::connect(&DockerConnection::new(DockerEndpoint::Socket(
+            socket_path.into(),
+        )))
+    }
+
+    /// Podman API socket, looked up in `CONTAINER_HOST`, then in the
+    /// rootless location under `XDG_RUNTIME_DIR` and finally in the rootful one.
+    pub fn podman_endpoint() -> DockerEndpoint {
+        if let Ok(container_host) = std::env::var(Self::PODMAN_HOST_ENV) {
+            return DockerEndpoint::Socket(container_host.into());
+        }
+        let rootless_socket = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| std::path::Path::new(&dir).join("podman/podman.sock"))
+            .filter(|socket| socket.exists());
+        DockerEndpoint::Socket(rootless_socket.unwrap_or(Self::PODMAN_ROOTFUL_SOCKET.into()))
+    }
+
+    /// Connects to the Podman API socket, see [`DockerRuntime::podman_endpoint`].
+    pub fn podman() -> anyhow::Result<Self> {
+        Self::connect(&DockerConnection::new(Self::podman_endpoint()))
+    }
+
+    pub fn client(&self) -> &bollard::Docker {
+        &self.docker
+    }
+
+    fn mount(mount: &Mount) -> bollard::models::Mount {
+        use bollard::models::MountTypeEnum;
+
+        let target = Some(mount.target().to_owned());
+        match mount {
+            Mount::Tmpfs { size_bytes, .. } => bollard::models::Mount {
+                target,
+                typ: Some(MountTypeEnum::TMPFS),
+                tmpfs_options: Some(bollard::models::MountTmpfsOptions {
+                    size_bytes: size_bytes.map(|size_bytes| size_bytes as i64),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            Mount::Volume { name, .. } => bollard::models::Mount {
+                target,
+                source: Some(name.to_string()),
+                typ: Some(MountTypeEnum::VOLUME),
+                ..Default::default()
+            },
+            Mount::Bind { host_path, .. } => bollard::models::Mount {
+                target,
+                source: Some(host_path.display().to_string()),
+                typ: Some(MountTypeEnum::BIND),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn volumes(&self) -> std::sync::MutexGuard<'_, Volumes> {
+        self.volumes
+            .lock()
+            .expect("docker runtime volumes poisoned")
+    }
+}
+
+impl super::ContainerRuntime for DockerRuntime {
+    fn ping(&self) -> RuntimeFuture<'_, ()> {
+        async move {
+            self.docker.ping().await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn create_container<'a>(
+        &'a self,
+        container_name: &'a str,
+        spec: &'a ContainerSpec,
+    ) -> RuntimeFuture<'a, ContainerId> {
+        async move {
+            let options = bollard::container::CreateContainerOptions {
+                name: container_name,
+                platform: None,
+            };
+            let env = spec.env.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+            let cmd = spec.cmd.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+            for mount in &spec.mounts {
+                if let Mount::Bind { host_path, .. } = mount {
+                    tokio::fs::create_dir_all(host_path).await?;
+                }
+            }
+            let mounts = spec.mounts.iter().map(Self::mount).collect::<Vec<_>>();
+            let ports = spec
+                .ports
+                .iter()
+                .map(|port| format!("{port}/tcp"))
+                .collect::<Vec<_>>();
+            // Empty host port lets the daemon pick a free one
+            let port_bindings = ports
+                .iter()
+                .map(|port| {
+                    let binding = bollard::models::PortBinding {
+                        host_ip: Some(Self::PUBLISH_HOST_IP.to_owned()),
+                        host_port: None,
+                    };
+                    (port.clone(), Some(vec![binding]))
+                })
+                .collect::<bollard::models::PortMap>();
+            let exposed_ports = ports
+                .iter()
+                .map(|port| (port.as_str(), Default::default()))
+                .collect::<std::collections::HashMap<_, _>>();
+            let config = bollard::container::Config {
+                image: Some(spec.image.as_ref()),
+                env: (!env.is_empty()).then_some(env),
+                cmd: (!cmd.is_empty()).then_some(cmd),
+                user: spec.user.as_deref(),
+                exposed_ports: (!exposed_ports.is_empty()).then_some(exposed_ports),
+                host_config: Some(bollard::models::HostConfig {
+                    mounts: (!mounts.is_empty()).then_some(mounts),
+                    port_bindings: (!port_bindings.is_empty()).then_some(port_bindings),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let container = self.docker.create_container(Some(options), config).await?;
+            let volumes = spec
+                .mounts
+                .iter()
+                .filter_map(|mount| match mount {
+                    Mount::Volume { name, .. } => Some(name.clone()),
+                    _ => None,
+                })
+                .collect();
+            self.volumes().insert(container_name.into(), volumes);
+            Ok(ContainerId(container.id.into_boxed_str()))
+        }
+        .boxed()
+    }
+
+    fn start_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let options = bollard::container::StartContainerOptions {
+                ..Default::default()
+            };
+            self.docker
+                .start_container::<String>(container_name, Some(options))
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn create_exec<'a>(
+        &'a self,
+        container_name: &'a str,
+        cmd: Vec<&'a str>,
+    ) -> RuntimeFuture<'a, ExecId> {
+        async move {
+            let config = bollard::exec::CreateExecOptions {
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                cmd: Some(cmd),
+                ..Default::default()
+            };
+            let exec = self.docker.create_exec(container_name, config).await?;
+            Ok(ExecId(exec.id.into_boxed_str()))
+        }
+        .boxed()
+    }
+
+    fn start_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ExecOutput> {
+        async move {
+            let options = bollard::exec::StartExecOptions {
+                detach: false,
+                ..Default::default()
+            };
+            let attach = self.docker.start_exec(&exec_id.0, Some(options)).await?;
+            let mut exec_output = ExecOutput::default();
+            if let bollard::exec::StartExecResults::Attached { mut output, .. } = attach {
+                while let Some(msg) = output.next().await {
+                    match msg? {
+                        bollard::container::LogOutput::StdErr { message } => {
+                            exec_output.stderr += &String::from_utf8_lossy(&message);
+                        }
+                        msg => exec_output.stdout += &msg.to_string(),
+                    }
+                }
+            }
+            let exec = self.docker.inspect_exec(&exec_id.0).await?;
+            exec_output.exit_code = exec.exit_code;
+            Ok(exec_output)
+        }
+        .boxed()
+    }
+
+    /// The API cannot signal an exec, so the whole container is killed
+    /// while the exec is still running.
+    fn kill_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ()> {
+        async move {
+            let exec = self.docker.inspect_exec(&exec_id.0).await?;
+            if let (Some(true), Some(container_id)) = (exec.running, exec.container_id) {
+                self.docker
+                    .kill_container::<String>(&container_id, None)
+                    .await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn upload_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        archive_path: std::path::PathBuf,
+        dest_path: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            let file = tokio::fs::File::open(archive_path)
+                .map_ok(ReaderStream::new)
+                .try_flatten_stream()
+                .map(|x| x.expect("failed to stream file"));
+            let options = bollard::container::UploadToContainerOptions {
+                path: dest_path.display().to_string(),
+                ..Default::default()
+            };
+            self.docker
+                .upload_to_container_streaming(container_name, Some(options), file)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn download_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        path: &'a std::path::Path,
+        dest_dir: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            let options = bollard::container::DownloadFromContainerOptions {
+                path: path.display().to_string(),
+            };
+            let mut stream = self
+                .docker
+                .download_from_container(container_name, Some(options));
+            let mut archive = vec![];
+            while let Some(chunk) = stream.next().await {
+                archive.extend_from_slice(&chunk?);
+            }
+            tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&dest_dir)?;
+                tar::Archive::new(archive.as_slice()).unpack(dest_dir)
+            })
+            .await??;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn logs<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, String> {
+        async move {
+            let options = bollard::container::LogsOptions {
+                stdout: true,
+                stderr: true,
+                tail: "all",
+                ..Default::default()
+            };
+            let mut stream = self.docker.logs(container_name, Some(options));
+            let mut logs = String::new();
+            while let Some(msg) = stream.next().await {
+                logs += &msg?.to_string();
+            }
+            Ok(logs)
+        }
+        .boxed()
+    }
+
+    fn host_port<'a>(
+        &'a self,
+        container_name: &'a str,
+        port: u16,
+    ) -> RuntimeFuture<'a, std::net::SocketAddr> {
+        async move {
+            let container = self.docker.inspect_container(container_name, None).await?;
+            let binding = container
+                .network_settings
+                .and_then(|settings| settings.ports)
+                .and_then(|mut ports| ports.remove(&format!("{port}/tcp")))
+                .flatten()
+                .and_then(|bindings| bindings.into_iter().next())
+                .ok_or(anyhow::anyhow!(
+                    "port {port} is not published: {container_name}"
+                ))?;
+            let host_ip = binding.host_ip.unwrap_or_default();
+            let host_ip = match host_ip.as_str() {
+                "" | "0.0.0.0" => Self::PUBLISH_HOST_IP,
+                host_ip => host_ip,
+            };
+            let host_port = binding.host_port.unwrap_or_default();
+            Ok(format!("{host_ip}:{host_port}").parse()?)
+        }
+        .boxed()
+    }
+
+    fn remove_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let options = bollard::container::RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            };
+            self.docker
+                .remove_container(container_name, Some(options))
+                .await?;
+            let volumes = self.volumes().remove(container_name).unwrap_or_default();
+            for volume in volumes {
+                self.docker.remove_volume(&volume, None).await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
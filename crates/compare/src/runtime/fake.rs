@@ -0,0 +1,360 @@
+use futures_util::FutureExt;
+
+use super::{ContainerId, ContainerSpec, ExecId, ExecOutput, RuntimeFuture};
+
+/// Operation recorded by [`FakeRuntime`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    CreateContainer {
+        container_name: Box<str>,
+        image: Box<str>,
+    },
+    StartContainer {
+        container_name: Box<str>,
+    },
+    CreateExec {
+        container_name: Box<str>,
+        cmd: Vec<Box<str>>,
+    },
+    StartExec {
+        container_name: Box<str>,
+        cmd: Vec<Box<str>>,
+    },
+    KillExec {
+        container_name: Box<str>,
+        cmd: Vec<Box<str>>,
+    },
+    UploadArchive {
+        container_name: Box<str>,
+        dest_path: std::path::PathBuf,
+        /// Paths of the archive entries
+        entries: Vec<std::path::PathBuf>,
+    },
+    DownloadArchive {
+        container_name: Box<str>,
+        path: std::path::PathBuf,
+    },
+    Logs {
+        container_name: Box<str>,
+    },
+    RemoveContainer {
+        container_name: Box<str>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum ExecScript {
+    Output(ExecOutput),
+    Error(Box<str>),
+    /// Never finishes, until killed
+    Hang,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    calls: Vec<Call>,
+    /// Created containers and whether they are running
+    containers: std::collections::HashMap<Box<str>, bool>,
+    specs: std::collections::HashMap<Box<str>, ContainerSpec>,
+    execs: std::collections::HashMap<ExecId, (Box<str>, Vec<Box<str>>)>,
+    scripts: Vec<(Box<str>, ExecScript)>,
+}
+
+/// In-memory runtime for tests.
+///
+/// Containers only exist as names, every call is recorded, and exec results
+/// are scripted by matching a substring of the joined command line. Execs
+/// without a matching script succeed with empty output and an unknown exit
+/// status, scripted hangs only end when the exec future is dropped.
+/// Published ports are reported on localhost with the same port number.
+/// Downloads create an empty file named after the requested path and logs
+/// are always empty.
+#[derive(Debug, Clone, Default)]
+pub struct FakeRuntime {
+    state: std::sync::Arc<std::sync::Mutex<FakeState>>,
+}
+
+impl FakeRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.state.lock().expect("fake runtime state poisoned")
+    }
+
+    /// Makes execs whose command line contains `pattern` return `output`.
+    ///
+    /// Later scripts take precedence over earlier ones.
+    pub fn script_exec(&self, pattern: &str, output: ExecOutput) -> &Self {
+        let script = ExecScript::Output(output);
+        self.state().scripts.push((pattern.into(), script));
+        self
+    }
+
+    /// Makes execs whose command line contains `pattern` fail with `message`.
+    pub fn fail_exec(&self, pattern: &str, message: &str) -> &Self {
+        let script = ExecScript::Error(message.into());
+        self.state().scripts.push((pattern.into(), script));
+        self
+    }
+
+    /// Makes execs whose command line contains `pattern` never finish.
+    pub fn hang_exec(&self, pattern: &str) -> &Self {
+        self.state()
+            .scripts
+            .push((pattern.into(), ExecScript::Hang));
+        self
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    /// Names of the containers which were created and not removed yet.
+    pub fn containers(&self) -> Vec<Box<str>> {
+        let mut containers = self.state().containers.keys().cloned().collect::<Vec<_>>();
+        containers.sort();
+        containers
+    }
+
+    /// Spec the container was created with.
+    pub fn container_spec(&self, container_name: &str) -> Option<ContainerSpec> {
+        self.state().specs.get(container_name).cloned()
+    }
+
+    /// Records the start of an exec and looks up its script.
+    fn exec_script(&self, exec_id: &ExecId) -> anyhow::Result<Option<ExecScript>> {
+        let mut state = self.state();
+        let (container_name, cmd) = state
+            .execs
+            .get(exec_id)
+            .cloned()
+            .ok_or(anyhow::anyhow!("no such exec: {}", exec_id.0))?;
+        state.calls.push(Call::StartExec {
+            container_name: container_name.clone(),
+            cmd: cmd.clone(),
+        });
+        anyhow::ensure!(
+            state.containers.get(&container_name) == Some(&true),
+            "container is not running: {container_name}"
+        );
+        let command_line = cmd.join(" ");
+        let script = state
+            .scripts
+            .iter()
+            .rev()
+            .find(|(pattern, _)| command_line.contains(pattern.as_ref()))
+            .map(|(_, script)| script.clone());
+        Ok(script)
+    }
+
+    fn archive_entries(archive_path: &std::path::Path) -> anyhow::Result<Vec<std::path::PathBuf>> {
+        let archive = std::fs::File::open(archive_path)?;
+        let mut archive = tar::Archive::new(archive);
+        let mut entries = vec![];
+        for entry in archive.entries()? {
+            entries.push(entry?.path()?.into_owned());
+        }
+        Ok(entries)
+    }
+}
+
+impl super::ContainerRuntime for FakeRuntime {
+    fn create_container<'a>(
+        &'a self,
+        container_name: &'a str,
+        spec: &'a ContainerSpec,
+    ) -> RuntimeFuture<'a, ContainerId> {
+        async move {
+            let mut state = self.state();
+            state.calls.push(Call::CreateContainer {
+                container_name: container_name.into(),
+                image: spec.image.clone(),
+            });
+            anyhow::ensure!(
+                !state.containers.contains_key(container_name),
+                "container name already in use: {container_name}"
+            );
+            state.containers.insert(container_name.into(), false);
+            state.specs.insert(container_name.into(), spec.clone());
+            Ok(ContainerId(
+                format!("fake-{container_name}").into_boxed_str(),
+            ))
+        }
+        .boxed()
+    }
+
+    fn start_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let mut state = self.state();
+            state.calls.push(Call::StartContainer {
+                container_name: container_name.into(),
+            });
+            let running = state
+                .containers
+                .get_mut(container_name)
+                .ok_or(anyhow::anyhow!("no such container: {container_name}"))?;
+            *running = true;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn create_exec<'a>(
+        &'a self,
+        container_name: &'a str,
+        cmd: Vec<&'a str>,
+    ) -> RuntimeFuture<'a, ExecId> {
+        async move {
+            let mut state = self.state();
+            let cmd = cmd.into_iter().map(Into::into).collect::<Vec<Box<str>>>();
+            state.calls.push(Call::CreateExec {
+                container_name: container_name.into(),
+                cmd: cmd.clone(),
+            });
+            anyhow::ensure!(
+                state.containers.get(container_name) == Some(&true),
+                "container is not running: {container_name}"
+            );
+            let exec_id = ExecId(format!("fake-exec-{}", state.execs.len()).into_boxed_str());
+            state
+                .execs
+                .insert(exec_id.clone(), (container_name.into(), cmd));
+            Ok(exec_id)
+        }
+        .boxed()
+    }
+
+    fn start_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ExecOutput> {
+        async move {
+            let script = self.exec_script(exec_id)?;
+            match script {
+                Some(ExecScript::Output(output)) => Ok(output),
+                Some(ExecScript::Error(message)) => Err(anyhow::anyhow!("{message}")),
+                Some(ExecScript::Hang) => futures_util::future::pending().await,
+                None => Ok(ExecOutput::default()),
+            }
+        }
+        .boxed()
+    }
+
+    fn kill_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ()> {
+        async move {
+            let mut state = self.state();
+            let (container_name, cmd) = state
+                .execs
+                .get(exec_id)
+                .cloned()
+                .ok_or(anyhow::anyhow!("no such exec: {}", exec_id.0))?;
+            state.calls.push(Call::KillExec {
+                container_name,
+                cmd,
+            });
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn upload_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        archive_path: std::path::PathBuf,
+        dest_path: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            let entries = Self::archive_entries(&archive_path)?;
+            let mut state = self.state();
+            state.calls.push(Call::UploadArchive {
+                container_name: container_name.into(),
+                dest_path,
+                entries,
+            });
+            anyhow::ensure!(
+                state.containers.contains_key(container_name),
+                "no such container: {container_name}"
+            );
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn download_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        path: &'a std::path::Path,
+        dest_dir: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            {
+                let mut state = self.state();
+                state.calls.push(Call::DownloadArchive {
+                    container_name: container_name.into(),
+                    path: path.to_path_buf(),
+                });
+                anyhow::ensure!(
+                    state.containers.contains_key(container_name),
+                    "no such container: {container_name}"
+                );
+            }
+            let file_name = path
+                .file_name()
+                .ok_or(anyhow::anyhow!("invalid download path: {}", path.display()))?;
+            std::fs::create_dir_all(&dest_dir)?;
+            std::fs::File::create(dest_dir.join(file_name))?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn logs<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, String> {
+        async move {
+            let mut state = self.state();
+            state.calls.push(Call::Logs {
+                container_name: container_name.into(),
+            });
+            anyhow::ensure!(
+                state.containers.contains_key(container_name),
+                "no such container: {container_name}"
+            );
+            Ok(String::new())
+        }
+        .boxed()
+    }
+
+    fn host_port<'a>(
+        &'a self,
+        container_name: &'a str,
+        port: u16,
+    ) -> RuntimeFuture<'a, std::net::SocketAddr> {
+        async move {
+            let state = self.state();
+            let spec = state
+                .specs
+                .get(container_name)
+                .filter(|_| state.containers.contains_key(container_name))
+                .ok_or(anyhow::anyhow!("no such container: {container_name}"))?;
+            anyhow::ensure!(
+                spec.ports.contains(&port),
+                "port {port} is not published: {container_name}"
+            );
+            Ok(std::net::SocketAddr::from(([127, 0, 0, 1], port)))
+        }
+        .boxed()
+    }
+
+    fn remove_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let mut state = self.state();
+            state.calls.push(Call::RemoveContainer {
+                container_name: container_name.into(),
+            });
+            state
+                .containers
+                .remove(container_name)
+                .ok_or(anyhow::anyhow!("no such container: {container_name}"))?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,542 @@
+use futures_util::FutureExt;
+
+use super::{ContainerId, ContainerSpec, ExecId, ExecOutput, Mount, RuntimeFuture};
+
+/// How to run an image as a local process.
+///
+/// Every argument may contain the [`LocalProgram::ROOT`] placeholder which is
+/// replaced by the directory standing in for the container filesystem. All
+/// commands run in the program working directory inside of it.
+#[derive(Debug, Clone, Default)]
+pub struct LocalProgram {
+    setup: Vec<Vec<Box<str>>>,
+    server: Vec<Box<str>>,
+    env: Vec<(Box<str>, Box<str>)>,
+    workdir: Option<Box<str>>,
+}
+
+impl LocalProgram {
+    pub const ROOT: &str = "{root}";
+
+    pub fn new<'a>(server: impl IntoIterator<Item = &'a str>) -> Self {
+        LocalProgram {
+            server: server.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a command which must succeed before the server is spawned.
+    pub fn setup<'a>(mut self, cmd: impl IntoIterator<Item = &'a str>) -> Self {
+        self.setup.push(cmd.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds an environment variable for the server and every exec.
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the working directory, as a path inside the container.
+    pub fn workdir(mut self, workdir: &str) -> Self {
+        self.workdir = Some(workdir.into());
+        self
+    }
+
+    /// `redis-server` keeping its files in `/data`, like the official image.
+    pub fn redis() -> Self {
+        LocalProgram::redis_compatible("redis-server")
+    }
+
+    /// Redis compatible `server` keeping its files in `/data`, like the
+    /// images of the other engines.
+    pub fn redis_compatible(server: &str) -> Self {
+        LocalProgram::new([server]).workdir("/data")
+    }
+
+    /// `postgres` with a fresh trust-auth cluster, listening on localhost and
+    /// on a unix socket in the container root.
+    pub fn postgres() -> Self {
+        LocalProgram::new([
+            "postgres",
+            "-D",
+            "{root}/var/lib/postgresql/data",
+            "-k",
+            "{root}",
+            "-c",
+            "listen_addresses=localhost",
+        ])
+        .setup([
+            "initdb",
+            "-D",
+            "{root}/var/lib/postgresql/data",
+            "-U",
+            "postgres",
+            "--auth=trust",
+        ])
+        .env("PGHOST", "{root}")
+        .env("PGUSER", "postgres")
+    }
+
+    /// Idle process standing in for an image without a server, whose
+    /// execs run `sqlite3` on the database file.
+    pub fn sqlite() -> Self {
+        LocalProgram::new(["tail", "-f", "/dev/null"])
+    }
+}
+
+struct LocalContainer {
+    root: std::path::PathBuf,
+    program: LocalProgram,
+    env: Vec<(Box<str>, Box<str>)>,
+    server: Option<tokio::process::Child>,
+    /// Directories backing mounts outside of `root`, removed with the container
+    mount_dirs: Vec<std::path::PathBuf>,
+}
+
+struct LocalExec {
+    container_name: Box<str>,
+    cmd: Vec<Box<str>>,
+}
+
+/// Runtime spawning database binaries directly on the host.
+///
+/// Each "container" is a directory under the runtime base directory, so the
+/// backends work on machines without a container daemon as long as the
+/// server and client binaries are on `PATH`. Mounts are emulated with
+/// symlinks: tmpfs points into `/dev/shm`, binds point to the host path and
+/// volumes are plain directories. Servers listen on the host directly, so a
+/// published port is the container port itself. Every process runs as the
+/// benchmark user, whatever the user of the spec.
+pub struct LocalProcessRuntime {
+    base_dir: std::path::PathBuf,
+    programs: std::collections::HashMap<Box<str>, LocalProgram>,
+    containers: std::sync::Mutex<std::collections::HashMap<Box<str>, LocalContainer>>,
+    execs: std::sync::Mutex<std::collections::HashMap<ExecId, LocalExec>>,
+    execs_count: std::sync::atomic::AtomicU64,
+}
+
+impl Default for LocalProcessRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalProcessRuntime {
+    const SERVER_LOG: &str = "server.log";
+    const SHM_DIR: &str = "/dev/shm";
+
+    pub fn new() -> Self {
+        LocalProcessRuntime {
+            base_dir: std::env::temp_dir().join("db-test-local"),
+            programs: Default::default(),
+            containers: Default::default(),
+            execs: Default::default(),
+            execs_count: 0.into(),
+        }
+        .with_program("redis", LocalProgram::redis())
+        .with_program("valkey", LocalProgram::redis_compatible("valkey-server"))
+        .with_program("keydb", LocalProgram::redis_compatible("keydb-server"))
+        .with_program("dragonfly", LocalProgram::redis_compatible("dragonfly"))
+        .with_program("postgres", LocalProgram::postgres())
+        .with_program("sqlite3", LocalProgram::sqlite())
+    }
+
+    pub fn with_base_dir(mut self, base_dir: impl Into<std::path::PathBuf>) -> Self {
+        self.base_dir = base_dir.into();
+        self
+    }
+
+    /// Registers the program used for containers created from `image`.
+    ///
+    /// Registry and tag are ignored when matching images.
+    pub fn with_program(mut self, image: &str, program: LocalProgram) -> Self {
+        self.programs.insert(image.into(), program);
+        self
+    }
+
+    fn containers(
+        &self,
+    ) -> std::sync::MutexGuard<'_, std::collections::HashMap<Box<str>, LocalContainer>> {
+        self.containers
+            .lock()
+            .expect("local runtime containers poisoned")
+    }
+
+    fn execs(&self) -> std::sync::MutexGuard<'_, std::collections::HashMap<ExecId, LocalExec>> {
+        self.execs.lock().expect("local runtime execs poisoned")
+    }
+
+    fn command(
+        root: &std::path::Path,
+        program: &LocalProgram,
+        env: &[(Box<str>, Box<str>)],
+        cmd: &[Box<str>],
+    ) -> anyhow::Result<tokio::process::Command> {
+        let root_str = root.display().to_string();
+        let expand = |arg: &str| arg.replace(LocalProgram::ROOT, &root_str);
+        let (binary, args) = cmd
+            .split_first()
+            .ok_or(anyhow::anyhow!("empty local command"))?;
+        let mut command = tokio::process::Command::new(expand(binary));
+        command
+            .args(args.iter().map(|arg| expand(arg)))
+            .envs(env.iter().map(|(key, value)| (key.as_ref(), expand(value))))
+            .current_dir(Self::workdir(root, program))
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+        Ok(command)
+    }
+
+    fn workdir(root: &std::path::Path, program: &LocalProgram) -> std::path::PathBuf {
+        match &program.workdir {
+            Some(workdir) => root.join(workdir.trim_start_matches('/')),
+            None => root.to_path_buf(),
+        }
+    }
+
+    /// Creates the directory backing `mount` and links it into the container root.
+    fn mount(
+        &self,
+        root: &std::path::Path,
+        container_name: &str,
+        mount: &Mount,
+    ) -> anyhow::Result<Option<std::path::PathBuf>> {
+        let target = root.join(mount.target().trim_start_matches('/'));
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let source = match mount {
+            Mount::Volume { .. } => {
+                std::fs::create_dir_all(&target)?;
+                return Ok(None);
+            }
+            Mount::Tmpfs { .. } => {
+                // `/var/lib/data` -> `{container_name}-var-lib-data`
+                let mount_name = mount.target().replace('/', "-");
+                let source = std::path::Path::new(Self::SHM_DIR)
+                    .join("db-test-local")
+                    .join(format!("{container_name}{mount_name}"));
+                if source.exists() {
+                    std::fs::remove_dir_all(&source)?;
+                }
+                source
+            }
+            Mount::Bind { host_path, .. } => host_path.clone(),
+        };
+        std::fs::create_dir_all(&source)?;
+        std::os::unix::fs::symlink(&source, &target)?;
+        Ok(matches!(mount, Mount::Tmpfs { .. }).then_some(source))
+    }
+
+    /// Copies a directory tree, following the symlinks emulating mounts.
+    fn copy_dir(src: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            let dest = dest.join(entry.file_name());
+            if entry.path().is_dir() {
+                Self::copy_dir(&entry.path(), &dest)?;
+            } else {
+                std::fs::copy(entry.path(), dest)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn container_env(
+        program: &LocalProgram,
+        spec_env: &[(Box<str>, Box<str>)],
+    ) -> Vec<(Box<str>, Box<str>)> {
+        program.env.iter().chain(spec_env).cloned().collect()
+    }
+}
+
+impl super::ContainerRuntime for LocalProcessRuntime {
+    fn create_container<'a>(
+        &'a self,
+        container_name: &'a str,
+        spec: &'a ContainerSpec,
+    ) -> RuntimeFuture<'a, ContainerId> {
+        async move {
+            let image = super::image_base_name(&spec.image);
+            let mut program = self.programs.get(image).cloned().ok_or(anyhow::anyhow!(
+                "no local program for image: {}",
+                spec.image
+            ))?;
+            if !spec.cmd.is_empty() {
+                program.server = spec.cmd.clone();
+            }
+            let env = spec
+                .env
+                .iter()
+                .filter_map(|var| var.split_once('='))
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect();
+            let root = self.base_dir.join(container_name);
+            if root.exists() {
+                tokio::fs::remove_dir_all(&root).await?;
+            }
+            tokio::fs::create_dir_all(&root).await?;
+            let mut mount_dirs = vec![];
+            for mount in &spec.mounts {
+                mount_dirs.extend(self.mount(&root, container_name, mount)?);
+            }
+            tokio::fs::create_dir_all(Self::workdir(&root, &program)).await?;
+            let container = LocalContainer {
+                root: root.clone(),
+                program,
+                env,
+                server: None,
+                mount_dirs,
+            };
+            self.containers().insert(container_name.into(), container);
+            Ok(ContainerId(root.display().to_string().into_boxed_str()))
+        }
+        .boxed()
+    }
+
+    fn start_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let (root, program, env) = {
+                let containers = self.containers();
+                let container = containers
+                    .get(container_name)
+                    .ok_or(anyhow::anyhow!("no such container: {container_name}"))?;
+                let env = Self::container_env(&container.program, &container.env);
+                (container.root.clone(), container.program.clone(), env)
+            };
+            for setup in &program.setup {
+                let output = Self::command(&root, &program, &env, setup)?
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "local setup command failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            let log = std::fs::File::create(root.join(Self::SERVER_LOG))?;
+            let server = Self::command(&root, &program, &env, &program.server)?
+                .stdout(log.try_clone()?)
+                .stderr(log)
+                .spawn()?;
+            if let Some(container) = self.containers().get_mut(container_name) {
+                container.server = Some(server);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn create_exec<'a>(
+        &'a self,
+        container_name: &'a str,
+        cmd: Vec<&'a str>,
+    ) -> RuntimeFuture<'a, ExecId> {
+        async move {
+            anyhow::ensure!(
+                self.containers().contains_key(container_name),
+                "no such container: {container_name}"
+            );
+            let exec_n = self
+                .execs_count
+                .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+            let exec_id = ExecId(format!("local-exec-{exec_n}").into_boxed_str());
+            let exec = LocalExec {
+                container_name: container_name.into(),
+                cmd: cmd.into_iter().map(Into::into).collect(),
+            };
+            self.execs().insert(exec_id.clone(), exec);
+            Ok(exec_id)
+        }
+        .boxed()
+    }
+
+    fn start_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ExecOutput> {
+        async move {
+            let exec = self
+                .execs()
+                .remove(exec_id)
+                .ok_or(anyhow::anyhow!("no such exec: {}", exec_id.0))?;
+            let (root, program, env) = {
+                let containers = self.containers();
+                let container = containers.get(&exec.container_name).ok_or(anyhow::anyhow!(
+                    "no such container: {}",
+                    exec.container_name
+                ))?;
+                let env = Self::container_env(&container.program, &container.env);
+                (container.root.clone(), container.program.clone(), env)
+            };
+            let output = Self::command(&root, &program, &env, &exec.cmd)?
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .output()
+                .await?;
+            Ok(ExecOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                exit_code: output.status.code().map(i64::from),
+            })
+        }
+        .boxed()
+    }
+
+    /// Execs are spawned with `kill_on_drop`, so dropping the abandoned
+    /// [`start_exec`](super::ContainerRuntime::start_exec) future already killed it.
+    fn kill_exec<'a>(&'a self, exec_id: &'a ExecId) -> RuntimeFuture<'a, ()> {
+        async move {
+            self.execs().remove(exec_id);
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn upload_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        archive_path: std::path::PathBuf,
+        dest_path: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            let dest_path = self.container_path(container_name, &dest_path);
+            tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&dest_path)?;
+                let archive = std::fs::File::open(archive_path)?;
+                tar::Archive::new(archive).unpack(dest_path)
+            })
+            .await??;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn download_archive<'a>(
+        &'a self,
+        container_name: &'a str,
+        path: &'a std::path::Path,
+        dest_dir: std::path::PathBuf,
+    ) -> RuntimeFuture<'a, ()> {
+        async move {
+            let src_path = self.container_path(container_name, path);
+            let file_name = path
+                .file_name()
+                .ok_or(anyhow::anyhow!("invalid download path: {}", path.display()))?
+                .to_owned();
+            tokio::task::spawn_blocking(move || {
+                let dest_path = dest_dir.join(file_name);
+                std::fs::create_dir_all(&dest_dir)?;
+                if src_path.is_dir() {
+                    Self::copy_dir(&src_path, &dest_path)
+                } else {
+                    std::fs::copy(&src_path, &dest_path).map(|_| ())
+                }
+            })
+            .await??;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn logs<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, String> {
+        async move {
+            let root = self.base_dir.join(container_name);
+            let logs = tokio::fs::read_to_string(root.join(Self::SERVER_LOG)).await?;
+            Ok(logs)
+        }
+        .boxed()
+    }
+
+    fn host_port<'a>(
+        &'a self,
+        container_name: &'a str,
+        port: u16,
+    ) -> RuntimeFuture<'a, std::net::SocketAddr> {
+        async move {
+            anyhow::ensure!(
+                self.containers().contains_key(container_name),
+                "no such container: {container_name}"
+            );
+            Ok(std::net::SocketAddr::from(([127, 0, 0, 1], port)))
+        }
+        .boxed()
+    }
+
+    fn remove_container<'a>(&'a self, container_name: &'a str) -> RuntimeFuture<'a, ()> {
+        async move {
+            let container = self
+                .containers()
+                .remove(container_name)
+                .ok_or(anyhow::anyhow!("no such container: {container_name}"))?;
+            if let Some(mut server) = container.server {
+                server.kill().await?;
+            }
+            tokio::fs::remove_dir_all(container.root).await?;
+            for mount_dir in container.mount_dirs {
+                tokio::fs::remove_dir_all(mount_dir).await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn container_path(&self, container_name: &str, path: &std::path::Path) -> std::path::PathBuf {
+        let relative_path = path.strip_prefix("/").unwrap_or(path);
+        self.base_dir.join(container_name).join(relative_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::ContainerRuntime;
+
+    #[tokio::test]
+    async fn exec_sees_uploaded_files_and_mounts() -> anyhow::Result<()> {
+        let base_dir = std::env::temp_dir().join("db-test-compare/local_runtime");
+        let runtime = LocalProcessRuntime::new()
+            .with_base_dir(&base_dir)
+            .with_program(
+                "sleeper",
+                LocalProgram::new(["sleep", "60"]).workdir("/data"),
+            );
+        let spec = ContainerSpec::new("example.org/sleeper:latest").mount(Mount::Volume {
+            target: "/data".into(),
+            name: "unused".into(),
+        });
+        runtime.create_container("sleeper-0", &spec).await?;
+        runtime.start_container("sleeper-0").await?;
+
+        let archive_path = base_dir.join("items.tar");
+        let mut archive = tar::Builder::new(std::fs::File::create(&archive_path)?);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        archive.append_data(&mut header, "items", &b"hello"[..])?;
+        archive.into_inner()?;
+        runtime
+            .upload_archive("sleeper-0", archive_path, "/tmp".into())
+            .await?;
+
+        let items = runtime.container_path("sleeper-0", std::path::Path::new("/tmp/items"));
+        let items = items.display().to_string();
+        let exec_id = runtime
+            .create_exec(
+                "sleeper-0",
+                vec!["bash", "-c", &format!("cat {items}; pwd")],
+            )
+            .await?;
+        let output = runtime.start_exec(&exec_id).await?;
+        let workdir = base_dir.join("sleeper-0/data");
+        assert_eq!(output.stdout, format!("hello{}\n", workdir.display()));
+        assert_eq!(output.exit_code, Some(0));
+
+        let exec_id = runtime
+            .create_exec("sleeper-0", vec!["bash", "-c", "exit 3"])
+            .await?;
+        assert_eq!(runtime.start_exec(&exec_id).await?.exit_code, Some(3));
+
+        runtime.remove_container("sleeper-0").await?;
+        assert!(!base_dir.join("sleeper-0").exists());
+        Ok(())
+    }
+}
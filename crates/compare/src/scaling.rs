@@ -0,0 +1,118 @@
+use crate::latency::Percentiles;
+
+/// Largest client count of the scaling benchmarks, which are skipped when unset.
+pub const MAX_CLIENTS_ENV: &str = "DB_TEST_MAX_CLIENTS";
+
+/// Reads `DB_TEST_MAX_CLIENTS`.
+pub fn max_clients_from_env() -> anyhow::Result<Option<usize>> {
+    match std::env::var(MAX_CLIENTS_ENV) {
+        Ok(max_clients) => match max_clients.parse() {
+            Ok(max_clients @ 1..) => Ok(Some(max_clients)),
+            _ => anyhow::bail!("invalid {MAX_CLIENTS_ENV}: {max_clients}"),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+/// Client counts measured up to `max_clients`: the powers of two below it,
+/// then `max_clients` itself.
+pub fn levels(max_clients: usize) -> Vec<usize> {
+    let mut levels = std::iter::successors(Some(1usize), |clients| clients.checked_mul(2))
+        .take_while(|clients| *clients < max_clients)
+        .collect::<Vec<_>>();
+    levels.push(max_clients.max(1));
+    levels
+}
+
+/// Aggregate throughput and latency at one client count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalingPoint {
+    pub clients: usize,
+    pub operations: u64,
+    pub elapsed: std::time::Duration,
+    pub latency: Option<Percentiles>,
+}
+
+impl ScalingPoint {
+    /// Operations per second over all clients.
+    pub fn throughput(&self) -> f64 {
+        self.operations as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// How the throughput of one backend and workload grows with its clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingCurve {
+    pub benchmark: String,
+    pub points: Vec<ScalingPoint>,
+}
+
+impl ScalingCurve {
+    pub fn new(benchmark: impl Into<String>) -> Self {
+        ScalingCurve {
+            benchmark: benchmark.into(),
+            points: vec![],
+        }
+    }
+
+    pub fn push(&mut self, point: ScalingPoint) {
+        self.points.push(point);
+    }
+
+    /// Throughput of `point` relative to the first measured point.
+    pub fn speedup(&self, point: &ScalingPoint) -> Option<f64> {
+        let first = self.points.first()?;
+        Some(point.throughput() / first.throughput())
+    }
+}
+
+impl std::fmt::Display for ScalingCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} scaling:", self.benchmark)?;
+        for point in &self.points {
+            write!(
+                f,
+                "\n  {:>4} clients: {:.0} ops/s",
+                point.clients,
+                point.throughput()
+            )?;
+            if let Some(speedup) = self.speedup(point) {
+                write!(f, " x{speedup:.2}")?;
+            }
+            if let Some(latency) = point.latency {
+                write!(f, ", {latency}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_double_up_to_max() {
+        assert_eq!(levels(1), vec![1]);
+        assert_eq!(levels(8), vec![1, 2, 4, 8]);
+        assert_eq!(levels(6), vec![1, 2, 4, 6]);
+    }
+
+    #[test]
+    fn curve_reports_speedup() {
+        let point = |clients, millis| ScalingPoint {
+            clients,
+            operations: 1000,
+            elapsed: std::time::Duration::from_millis(millis),
+            latency: None,
+        };
+        let mut curve = ScalingCurve::new("insert_bulk/redis");
+        curve.push(point(1, 1000));
+        curve.push(point(2, 400));
+        assert_eq!(curve.speedup(&curve.points[1]), Some(2.5));
+        assert_eq!(
+            curve.to_string(),
+            "insert_bulk/redis scaling:\n     1 clients: 1000 ops/s x1.00\n     2 clients: 2500 ops/s x2.50"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::runtime::Mount;
+
+/// Where a backend keeps its data directory.
+///
+/// Lets disk-bound runs be told apart from in-memory ones instead of
+/// depending on whatever storage driver the daemon happens to use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Storage {
+    /// Container writable layer
+    #[default]
+    Overlay,
+    /// In-memory filesystem
+    Tmpfs { size_bytes: Option<u64> },
+    /// Fresh `{prefix}-{container_name}` volume per container
+    Volume { prefix: Box<str> },
+    /// `{host_dir}/{container_name}` host directory per container, kept after the run
+    Bind { host_dir: std::path::PathBuf },
+}
+
+impl Storage {
+    pub const STORAGE_ENV: &str = "DB_TEST_STORAGE";
+    const DEFAULT_VOLUME_PREFIX: &str = "db-test";
+
+    /// Reads the storage from `DB_TEST_STORAGE`, see [`Storage::from_str`]
+    /// for the format. Defaults to [`Storage::Overlay`].
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(Self::STORAGE_ENV) {
+            Ok(storage) => storage.parse(),
+            Err(_) => Ok(Storage::Overlay),
+        }
+    }
+
+    /// Short name used in benchmark ids.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Storage::Overlay => "overlay",
+            Storage::Tmpfs { .. } => "tmpfs",
+            Storage::Volume { .. } => "volume",
+            Storage::Bind { .. } => "bind",
+        }
+    }
+
+    /// Mount placing `data_dir` of the container on this storage.
+    pub fn mount(&self, container_name: &str, data_dir: &str) -> Option<Mount> {
+        let target = data_dir.into();
+        match self {
+            Storage::Overlay => None,
+            Storage::Tmpfs { size_bytes } => Some(Mount::Tmpfs {
+                target,
+                size_bytes: *size_bytes,
+            }),
+            Storage::Volume { prefix } => Some(Mount::Volume {
+                target,
+                name: format!("{prefix}-{container_name}").into_boxed_str(),
+            }),
+            Storage::Bind { host_dir } => Some(Mount::Bind {
+                target,
+                host_path: host_dir.join(container_name),
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for Storage {
+    type Err = anyhow::Error;
+
+    /// Parses `overlay`, `tmpfs[:<size bytes>]`, `volume[:<prefix>]` or `bind:<host dir>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, arg) = match s.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg)),
+            None => (s, None),
+        };
+        let storage = match (kind, arg) {
+            ("overlay", None) => Storage::Overlay,
+            ("tmpfs", size_bytes) => Storage::Tmpfs {
+                size_bytes: size_bytes.map(str::parse).transpose()?,
+            },
+            ("volume", prefix) => Storage::Volume {
+                prefix: prefix.unwrap_or(Self::DEFAULT_VOLUME_PREFIX).into(),
+            },
+            ("bind", Some(host_dir)) => Storage::Bind {
+                host_dir: host_dir.into(),
+            },
+            _ => anyhow::bail!("invalid storage: {s}"),
+        };
+        Ok(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_storage() -> anyhow::Result<()> {
+        assert_eq!("overlay".parse::<Storage>()?, Storage::Overlay);
+        assert_eq!(
+            "tmpfs:1024".parse::<Storage>()?,
+            Storage::Tmpfs {
+                size_bytes: Some(1024)
+            }
+        );
+        assert_eq!(
+            "volume".parse::<Storage>()?,
+            Storage::Volume {
+                prefix: "db-test".into()
+            }
+        );
+        assert_eq!(
+            "bind:/srv/bench".parse::<Storage>()?,
+            Storage::Bind {
+                host_dir: "/srv/bench".into()
+            }
+        );
+        assert!("bind".parse::<Storage>().is_err());
+        assert!("tmpfs:lots".parse::<Storage>().is_err());
+        Ok(())
+    }
+}
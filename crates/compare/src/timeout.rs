@@ -0,0 +1,266 @@
+use crate::runtime::{ExecId, ExecOutput, Runtime};
+
+/// Part of a benchmark run, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// Creating and starting the container
+    Provision,
+    /// Waiting for the server to accept clients
+    Ready,
+    /// Uploading the dataset and loading what the measurement needs in place
+    LoadFixture,
+    /// Untimed operations bringing caches to their steady state
+    WarmUp,
+    /// Measured load
+    Measure,
+    /// Checking the loaded data against the dataset
+    Verify,
+    /// Measuring the loaded data
+    Footprint,
+    /// Removing the container
+    Teardown,
+}
+
+impl Phase {
+    pub const ALL: &[Phase] = &[
+        Phase::Provision,
+        Phase::Ready,
+        Phase::LoadFixture,
+        Phase::WarmUp,
+        Phase::Measure,
+        Phase::Verify,
+        Phase::Footprint,
+        Phase::Teardown,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Provision => "provision",
+            Phase::Ready => "ready",
+            Phase::LoadFixture => "load-fixture",
+            Phase::WarmUp => "warm-up",
+            Phase::Measure => "measure",
+            Phase::Footprint => "footprint",
+            Phase::Verify => "verify",
+            Phase::Teardown => "teardown",
+        }
+    }
+
+    fn default_limit(&self) -> std::time::Duration {
+        let secs = match self {
+            Phase::Provision => 120,
+            Phase::Ready => 30,
+            Phase::LoadFixture => 600,
+            Phase::WarmUp => 300,
+            Phase::Measure => 1800,
+            Phase::Footprint => 120,
+            Phase::Verify => 120,
+            Phase::Teardown => 120,
+        };
+        std::time::Duration::from_secs(secs)
+    }
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Phase {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|phase| phase.name() == s)
+            .copied()
+            .ok_or(anyhow::anyhow!("invalid phase: {s}"))
+    }
+}
+
+/// Error returned when a phase, or an exec within it, runs out of time.
+///
+/// Can be told apart from other failures with `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout {
+    pub phase: Phase,
+    pub limit: std::time::Duration,
+}
+
+impl std::fmt::Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} timed out after {:?}", self.phase, self.limit)
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+/// Limits for single execs and for whole phases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeouts {
+    pub exec: std::time::Duration,
+    phases: std::collections::HashMap<Phase, std::time::Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            exec: Self::DEFAULT_EXEC_LIMIT,
+            phases: Default::default(),
+        }
+    }
+}
+
+impl Timeouts {
+    pub const TIMEOUTS_ENV: &str = "DB_TEST_TIMEOUTS";
+    const DEFAULT_EXEC_LIMIT: std::time::Duration = std::time::Duration::from_secs(1800);
+
+    /// Reads `DB_TEST_TIMEOUTS`, see [`Timeouts::from_str`] for the format.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(Self::TIMEOUTS_ENV) {
+            Ok(timeouts) => timeouts.parse(),
+            Err(_) => Ok(Timeouts::default()),
+        }
+    }
+
+    pub fn with_exec(mut self, limit: std::time::Duration) -> Self {
+        self.exec = limit;
+        self
+    }
+
+    pub fn with_phase(mut self, phase: Phase, limit: std::time::Duration) -> Self {
+        self.phases.insert(phase, limit);
+        self
+    }
+
+    /// Sets the limit of `phase` unless one is configured already.
+    pub fn with_default_phase(mut self, phase: Phase, limit: std::time::Duration) -> Self {
+        self.phases.entry(phase).or_insert(limit);
+        self
+    }
+
+    pub fn phase(&self, phase: Phase) -> std::time::Duration {
+        match self.phases.get(&phase) {
+            Some(limit) => *limit,
+            None => phase.default_limit(),
+        }
+    }
+
+    /// Runs `future` as `phase`, failing with [`Timeout`] once the phase limit is exceeded.
+    ///
+    /// The future is dropped on timeout, which cancels whatever it was waiting for.
+    pub async fn within<T>(
+        &self,
+        phase: Phase,
+        future: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let limit = self.phase(phase);
+        match tokio::time::timeout(limit, future).await {
+            Ok(output) => output,
+            Err(_) => Err(Timeout { phase, limit }.into()),
+        }
+    }
+
+    /// Runs an exec as part of `phase`, killing it once either the exec or
+    /// the phase limit is exceeded.
+    pub async fn exec(
+        &self,
+        runtime: &Runtime,
+        exec_id: &ExecId,
+        phase: Phase,
+    ) -> anyhow::Result<ExecOutput> {
+        let limit = self.exec.min(self.phase(phase));
+        match tokio::time::timeout(limit, runtime.start_exec(exec_id)).await {
+            Ok(output) => output,
+            Err(_) => {
+                if let Err(err) = runtime.kill_exec(exec_id).await {
+                    eprintln!("cannot kill exec {}: {err:#}", exec_id.as_str());
+                }
+                Err(Timeout { phase, limit }.into())
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Timeouts {
+    type Err = anyhow::Error;
+
+    /// Parses comma separated `exec=<secs>` and `<phase>=<secs>` limits,
+    /// e.g. `exec=60,measure=600`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut timeouts = Timeouts::default();
+        for limit in s.split(',').filter(|limit| !limit.is_empty()) {
+            let (key, secs) = limit
+                .split_once('=')
+                .ok_or(anyhow::anyhow!("invalid timeout: {limit}"))?;
+            let secs = std::time::Duration::from_secs(secs.parse()?);
+            timeouts = match key {
+                "exec" => timeouts.with_exec(secs),
+                phase => timeouts.with_phase(phase.parse()?, secs),
+            };
+        }
+        Ok(timeouts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{ContainerSpec, FakeRuntime, fake::Call};
+
+    #[test]
+    fn parse_timeouts() -> anyhow::Result<()> {
+        let timeouts = "exec=60,measure=600".parse::<Timeouts>()?;
+        assert_eq!(timeouts.exec, std::time::Duration::from_secs(60));
+        assert_eq!(
+            timeouts.phase(Phase::Measure),
+            std::time::Duration::from_secs(600)
+        );
+        assert_eq!(timeouts.phase(Phase::Ready), Phase::Ready.default_limit());
+        assert!("measure".parse::<Timeouts>().is_err());
+        assert!("cleanup=1".parse::<Timeouts>().is_err());
+
+        let slow_ready = std::time::Duration::from_secs(300);
+        let timeouts = timeouts.with_default_phase(Phase::Ready, slow_ready);
+        assert_eq!(timeouts.phase(Phase::Ready), slow_ready);
+        let timeouts = timeouts.with_default_phase(Phase::Measure, slow_ready);
+        assert_eq!(
+            timeouts.phase(Phase::Measure),
+            std::time::Duration::from_secs(600)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hung_exec_is_killed() -> anyhow::Result<()> {
+        let fake = FakeRuntime::new();
+        fake.hang_exec("redis-cli");
+        let runtime = Runtime::new(fake.clone());
+        runtime
+            .create_container("hung", &ContainerSpec::new("image"))
+            .await?;
+        runtime.start_container("hung").await?;
+        let exec_id = runtime.create_exec("hung", vec!["redis-cli"]).await?;
+        let timeouts = Timeouts::default().with_exec(std::time::Duration::from_millis(10));
+        let err = timeouts
+            .exec(&runtime, &exec_id, Phase::Footprint)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Timeout>(),
+            Some(&Timeout {
+                phase: Phase::Footprint,
+                limit: std::time::Duration::from_millis(10),
+            })
+        );
+        assert_eq!(
+            fake.calls().last(),
+            Some(&Call::KillExec {
+                container_name: "hung".into(),
+                cmd: vec!["redis-cli".into()],
+            })
+        );
+        Ok(())
+    }
+}
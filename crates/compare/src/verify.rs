@@ -0,0 +1,171 @@
+//! Checks shared by the [`Backend::verify`](crate::Backend::verify) implementations.
+
+use db_test_model::DataRow;
+
+use crate::workload::WorkloadInput;
+
+/// Number of dataset rows looked up in the loaded data.
+pub const SAMPLE_SIZE: u64 = 10;
+
+/// Fails unless the loaded data holds the `expected` amount of `what`.
+pub fn ensure_count(what: &str, expected: u64, actual: u64) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        expected == actual,
+        "verification failed: expected {expected} {what}, found {actual}"
+    );
+    Ok(())
+}
+
+/// Fails if the loader reported errors or a row count other than `expected`.
+pub fn ensure_loader(loader: &crate::LoaderReport, expected: u64) -> anyhow::Result<()> {
+    ensure_count("loader errors", 0, loader.errors)?;
+    if let Some(rows_loaded) = loader.rows_loaded {
+        ensure_count("rows reported by the loader", expected, rows_loaded)?;
+    }
+    Ok(())
+}
+
+/// Checks a load into the `user_transactions` table: the rows and distinct
+/// users `counts` returns, then the timestamp `trans_time` reads back for
+/// every sampled row, as printed by the client.
+pub async fn stored_rows<T>(
+    input: &WorkloadInput,
+    loader: &crate::LoaderReport,
+    counts: impl Future<Output = anyhow::Result<(u64, u64)>>,
+    trans_time: impl Fn(&DataRow) -> T,
+) -> anyhow::Result<()>
+where
+    T: Future<Output = anyhow::Result<String>>,
+{
+    ensure_loader(loader, input.operations_count()?)?;
+    let expected = db_test_model::sample_data_file(&input.file_path, SAMPLE_SIZE)?;
+    let (rows, users) = counts.await?;
+    ensure_count("rows", input.expected_rows()?, rows)?;
+    ensure_count("users", expected.users, users)?;
+
+    for row in &expected.sample {
+        let trans_time = trans_time(row).await?;
+        anyhow::ensure!(
+            trans_time == row.timestamp,
+            "verification failed: {row:?} is stored at {trans_time:?}"
+        );
+    }
+    Ok(())
+}
+
+/// [`stored_rows`] through SQL, `query` returning one line per row with its
+/// columns split by `separator`.
+pub async fn sql_rows<Q>(
+    input: &WorkloadInput,
+    loader: &crate::LoaderReport,
+    separator: char,
+    query: impl Fn(String) -> Q,
+) -> anyhow::Result<()>
+where
+    Q: Future<Output = anyhow::Result<String>>,
+{
+    let sql = "select count(*), count(distinct user_addr) from user_transactions";
+    let counts = query(sql.into());
+    let counts = async {
+        let counts = counts.await?;
+        let (rows, users) = counts
+            .trim()
+            .split_once(separator)
+            .ok_or(anyhow::anyhow!("invalid counts: {counts:?}"))?;
+        Ok((rows.parse()?, users.parse()?))
+    };
+    let trans_time = |row: &DataRow| {
+        let sql = format!(
+            "select trans_time from user_transactions \
+             where user_addr = {} and trans_hash = {}",
+            literal(&row.user),
+            literal(&row.transaction_id)
+        );
+        let trans_time = query(sql);
+        async { Ok(trans_time.await?.trim().to_owned()) }
+    };
+    stored_rows(input, loader, counts, trans_time).await
+}
+
+/// Quotes `value` as an SQL string literal.
+///
+/// MySQL and ClickHouse also read backslashes as escapes, which the hex
+/// addresses and hashes of the datasets never hold.
+pub fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loader_mismatch_fails() {
+        let loader = crate::LoaderReport {
+            rows_loaded: Some(9),
+            ..Default::default()
+        };
+        assert!(ensure_loader(&loader, 10).is_err());
+        assert!(ensure_loader(&loader, 9).is_ok());
+        let loader = crate::LoaderReport {
+            errors: 1,
+            ..Default::default()
+        };
+        assert!(ensure_loader(&loader, 10).is_err());
+    }
+
+    #[tokio::test]
+    async fn sql_rows_reads_back_sample() -> anyhow::Result<()> {
+        let file_path = crate::testing::gen_test_csv("verify_sql_rows_reads_back_sample", 100)?;
+        let input = WorkloadInput::new(crate::workload::Workload::BulkInsert, file_path);
+        let db = rusqlite::Connection::open_in_memory()?;
+        db.execute_batch(
+            "create table user_transactions \
+             (user_addr text not null, trans_time bigint not null, trans_hash text not null)",
+        )?;
+        for row in db_test_model::read_data_file(&input.file_path)? {
+            db.execute(
+                "insert into user_transactions values (?1, ?2, ?3)",
+                (
+                    &row.user,
+                    row.timestamp.parse::<i64>()?,
+                    &row.transaction_id,
+                ),
+            )?;
+        }
+        let query = |sql: String| {
+            let rows = (|| {
+                let mut statement = db.prepare(&sql)?;
+                let columns = statement.column_count();
+                let mut rows = statement.query([])?;
+                let mut output = String::new();
+                while let Some(row) = rows.next()? {
+                    let values = (0..columns)
+                        .map(|i| row.get::<_, rusqlite::types::Value>(i))
+                        .map(|value| match value? {
+                            rusqlite::types::Value::Integer(value) => Ok(value.to_string()),
+                            rusqlite::types::Value::Text(value) => Ok(value),
+                            value => anyhow::bail!("unexpected value: {value:?}"),
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    output.push_str(&values.join("|"));
+                    output.push('\n');
+                }
+                Ok(output)
+            })();
+            std::future::ready(rows)
+        };
+        let loader = crate::LoaderReport::default();
+        sql_rows(&input, &loader, '|', &query).await?;
+
+        // A lost row fails the verification
+        db.execute("delete from user_transactions where rowid = 1", [])?;
+        assert!(sql_rows(&input, &loader, '|', &query).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn literal_doubles_quotes() {
+        assert_eq!(literal("it's"), "'it''s'");
+    }
+}
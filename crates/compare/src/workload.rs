@@ -0,0 +1,247 @@
+use db_test_model::DataRow;
+
+/// Kind of load a benchmark puts on a backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Workload {
+    /// Whole dataset loaded by the bulk loading tool of the database
+    BulkInsert,
+    /// Transaction looked up by user and id
+    PointRead,
+    /// Transactions of a user within a time window
+    RangeScan,
+    /// Count, first and last timestamp of the transactions of a user
+    Aggregation,
+    /// Point reads interleaved with inserts of new transactions
+    Mixed,
+}
+
+impl Workload {
+    pub const WORKLOADS_ENV: &str = "DB_TEST_WORKLOADS";
+    pub const ALL: &[Workload] = &[
+        Workload::BulkInsert,
+        Workload::PointRead,
+        Workload::RangeScan,
+        Workload::Aggregation,
+        Workload::Mixed,
+    ];
+
+    /// Short name used in benchmark ids.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::BulkInsert => "insert_bulk",
+            Workload::PointRead => "point_read",
+            Workload::RangeScan => "range_scan",
+            Workload::Aggregation => "aggregation",
+            Workload::Mixed => "mixed",
+        }
+    }
+
+    /// Reads comma separated workload names from `DB_TEST_WORKLOADS`,
+    /// every workload is selected when unset.
+    pub fn from_env() -> anyhow::Result<Vec<Workload>> {
+        match std::env::var(Self::WORKLOADS_ENV) {
+            Ok(workloads) => workloads
+                .split(',')
+                .map(str::trim)
+                .filter(|workload| !workload.is_empty())
+                .map(str::parse)
+                .collect(),
+            Err(_) => Ok(Self::ALL.to_vec()),
+        }
+    }
+}
+
+impl std::fmt::Display for Workload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Workload {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|workload| workload.name() == s)
+            .copied()
+            .ok_or(anyhow::anyhow!("invalid workload: {s}"))
+    }
+}
+
+/// Single measured operation of the non bulk workloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Insert(DataRow),
+    PointRead {
+        user: String,
+        transaction_id: String,
+    },
+    /// Inclusive timestamp window
+    RangeScan {
+        user: String,
+        from: u64,
+        to: u64,
+    },
+    Aggregate {
+        user: String,
+    },
+}
+
+/// Dataset a workload runs against.
+///
+/// Except for [`Workload::BulkInsert`], the dataset is loaded before the
+/// measurement and only the [`WorkloadInput::operations`] are timed.
+#[derive(Debug, Clone)]
+pub struct WorkloadInput {
+    pub workload: Workload,
+    pub file_path: std::path::PathBuf,
+    /// Concurrent clients the operations are split across, only the
+    /// [`LoadMode::Driver`](crate::LoadMode::Driver) runs more than one
+    pub clients: usize,
+}
+
+impl WorkloadInput {
+    /// Upper bound of the operations measured per run.
+    pub const OPERATIONS: u64 = 1000;
+    /// Half width of the [`Operation::RangeScan`] windows, in seconds
+    const RANGE_SCAN_WINDOW: u64 = 30 * 24 * 60 * 60;
+
+    pub fn new(workload: Workload, file_path: impl Into<std::path::PathBuf>) -> Self {
+        WorkloadInput {
+            workload,
+            file_path: file_path.into(),
+            clients: 1,
+        }
+    }
+
+    pub fn with_clients(mut self, clients: usize) -> Self {
+        self.clients = clients;
+        self
+    }
+
+    /// Operations timed by the workload, derived from rows spread over the
+    /// dataset, so they are the same on every call. Empty for bulk inserts.
+    pub fn operations(&self) -> anyhow::Result<Vec<Operation>> {
+        if self.workload == Workload::BulkInsert {
+            return Ok(vec![]);
+        }
+        let sample = db_test_model::sample_data_file(&self.file_path, Self::OPERATIONS)?.sample;
+        let mut operations = vec![];
+        for row in sample {
+            let operation = match self.workload {
+                Workload::BulkInsert => unreachable!(),
+                Workload::PointRead => Self::point_read(&row),
+                Workload::RangeScan => {
+                    let timestamp: u64 = row.timestamp.parse()?;
+                    Operation::RangeScan {
+                        user: row.user,
+                        from: timestamp.saturating_sub(Self::RANGE_SCAN_WINDOW),
+                        to: timestamp + Self::RANGE_SCAN_WINDOW,
+                    }
+                }
+                Workload::Aggregation => Operation::Aggregate { user: row.user },
+                Workload::Mixed => {
+                    operations.push(Self::point_read(&row));
+                    Operation::Insert(Self::new_transaction(row))
+                }
+            };
+            operations.push(operation);
+        }
+        Ok(operations)
+    }
+
+    /// Operations a client issues one at a time: every row inserted for
+    /// bulk inserts, [`WorkloadInput::operations`] otherwise.
+    pub fn client_operations(&self) -> anyhow::Result<Vec<Operation>> {
+        match self.workload {
+            Workload::BulkInsert => {
+                let rows = db_test_model::read_data_file(&self.file_path)?;
+                Ok(rows.into_iter().map(Operation::Insert).collect())
+            }
+            _ => self.operations(),
+        }
+    }
+
+    /// Operations counted by the throughput, rows for bulk inserts.
+    pub fn operations_count(&self) -> anyhow::Result<u64> {
+        match self.workload {
+            Workload::BulkInsert => db_test_model::count_data_rows(&self.file_path),
+            _ => Ok(self.operations()?.len() as u64),
+        }
+    }
+
+    /// Rows the backend holds once the workload is done.
+    pub fn expected_rows(&self) -> anyhow::Result<u64> {
+        let rows = db_test_model::count_data_rows(&self.file_path)?;
+        let inserts = self
+            .operations()?
+            .iter()
+            .filter(|operation| matches!(operation, Operation::Insert(_)))
+            .count();
+        Ok(rows + inserts as u64)
+    }
+
+    fn point_read(row: &DataRow) -> Operation {
+        Operation::PointRead {
+            user: row.user.clone(),
+            transaction_id: row.transaction_id.clone(),
+        }
+    }
+
+    /// Another transaction of the same user at the same time, with the id
+    /// reversed so that it is new but still deterministic.
+    fn new_transaction(row: DataRow) -> DataRow {
+        DataRow {
+            transaction_id: row.transaction_id.chars().rev().collect(),
+            ..row
+        }
+    }
+}
+
+/// Rows per statement or transaction of the batched loads, unless configured.
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Reads the batch size of the batched loads of a backend from `var`.
+pub fn batch_size_from_env(var: &str) -> anyhow::Result<usize> {
+    match std::env::var(var) {
+        Ok(batch_size) => match batch_size.parse() {
+            Ok(batch_size @ 1..) => Ok(batch_size),
+            _ => anyhow::bail!("invalid {var}: {batch_size}"),
+        },
+        Err(_) => Ok(DEFAULT_BATCH_SIZE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_workloads() -> anyhow::Result<()> {
+        for workload in Workload::ALL {
+            assert_eq!(workload.name().parse::<Workload>()?, *workload);
+        }
+        assert!("scan".parse::<Workload>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_interleaves_reads_and_inserts() -> anyhow::Result<()> {
+        let file_path = crate::testing::gen_test_csv("workload_mixed_operations", 20)?;
+        let input = WorkloadInput::new(Workload::Mixed, &file_path);
+        let operations = input.operations()?;
+        assert_eq!(operations.len(), 40);
+        assert!(matches!(operations[0], Operation::PointRead { .. }));
+        assert!(matches!(operations[1], Operation::Insert(_)));
+        assert_eq!(input.operations()?, operations);
+        assert_eq!(input.expected_rows()?, 40);
+        assert_eq!(input.operations_count()?, 40);
+
+        let input = WorkloadInput::new(Workload::BulkInsert, &file_path);
+        assert!(input.operations()?.is_empty());
+        assert_eq!(input.operations_count()?, 20);
+        assert_eq!(input.expected_rows()?, 20);
+        Ok(())
+    }
+}
@@ -34,16 +34,11 @@ impl Timestamp {
     fn new_random(rng: &mut impl Rng) -> Self {
         Timestamp(rng.random_range(0..Self::BOUNDARY))
     }
+}
 
-    fn as_bytes(&self) -> &[u8] {
-        let value = &self.0;
-        let ptr = value as *const u64 as *const u8;
-        let len = std::mem::size_of::<u64>();
-        unsafe {
-            // SAFETY
-            // This is the reference to `u64` value. Everything is good.
-            std::slice::from_raw_parts(ptr, len)
-        }
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
     }
 }
 
@@ -73,7 +68,7 @@ impl Transaction {
         W: std::io::Write,
     {
         writer.write_field(self.0.as_bytes())?;
-        writer.write_field(self.1.as_bytes())?;
+        writer.write_field(self.1.to_string())?;
         writer.write_field(self.2.as_bytes())?;
         writer.write_record(None::<&[u8]>)?;
         Ok(())
@@ -85,6 +80,12 @@ pub struct BulkDataGenerator {
     major_pool: Vec<UserAddr>,
 }
 
+impl Default for BulkDataGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BulkDataGenerator {
     const MAJOR_USERS: Ratio<u32> = Ratio::new_raw(1, 100);
     const MAJOR_TRANSACTIONS: Ratio<u32> = Ratio::new_raw(50, 100);
@@ -104,7 +105,7 @@ impl BulkDataGenerator {
         let mut user_addr = None;
         if self.random_ratio(Self::MAJOR_TRANSACTIONS) {
             let major_user = self.major_pool.choose(&mut self.rng);
-            user_addr = major_user.map(|major_user| Cow::Borrowed(major_user));
+            user_addr = major_user.map(Cow::Borrowed);
         }
         if let Some(user_addr) = user_addr {
             user_addr
@@ -41,3 +41,122 @@ pub fn list_data_files() -> anyhow::Result<impl Iterator<Item = std::path::PathB
     }
     Ok(files.into_iter())
 }
+
+/// Quotes `value` as a JSON string.
+pub fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes a transaction as a single line Extended JSON document, with the
+/// field names of the SQL schemas.
+///
+/// The timestamp is a `$numberLong`, so that it is stored as a 64-bit
+/// integer like the `bigint` columns even though every value fits 32 bits.
+pub fn write_json_document(
+    writer: &mut impl std::io::Write,
+    user: &str,
+    timestamp: u64,
+    transaction_id: &str,
+) -> anyhow::Result<()> {
+    writeln!(
+        writer,
+        "{{\"user_addr\":{},\"trans_time\":{{\"$numberLong\":\"{timestamp}\"}},\"trans_hash\":{}}}",
+        json_string(user),
+        json_string(transaction_id)
+    )?;
+    Ok(())
+}
+
+/// Number of transactions in a data file.
+pub fn count_data_rows(file_path: &std::path::Path) -> anyhow::Result<u64> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(file_path)?;
+    let mut rows = 0;
+    for record in reader.records() {
+        record?;
+        rows += 1;
+    }
+    Ok(rows)
+}
+
+/// Transaction as written in a data file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataRow {
+    pub user: String,
+    pub timestamp: String,
+    pub transaction_id: String,
+}
+
+/// What a loaded data file is checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSample {
+    pub rows: u64,
+    /// Distinct users
+    pub users: u64,
+    /// Rows spread evenly over the file
+    pub sample: Vec<DataRow>,
+}
+
+/// Counts rows and users of a data file and picks up to `sample_size` rows.
+pub fn sample_data_file(
+    file_path: &std::path::Path,
+    sample_size: u64,
+) -> anyhow::Result<DataSample> {
+    let rows = count_data_rows(file_path)?;
+    let stride = (rows / sample_size.max(1)).max(1);
+    let mut users = std::collections::HashSet::new();
+    let mut sample = vec![];
+    for (i, row) in data_rows(file_path)?.enumerate() {
+        let row = row?;
+        if !users.contains(&row.user) {
+            users.insert(row.user.clone());
+        }
+        if (i as u64).is_multiple_of(stride) && (sample.len() as u64) < sample_size {
+            sample.push(row);
+        }
+    }
+    Ok(DataSample {
+        rows,
+        users: users.len() as u64,
+        sample,
+    })
+}
+
+/// Every row of a data file, in file order.
+pub fn read_data_file(file_path: &std::path::Path) -> anyhow::Result<Vec<DataRow>> {
+    data_rows(file_path)?.collect()
+}
+
+fn data_rows(
+    file_path: &std::path::Path,
+) -> anyhow::Result<impl Iterator<Item = anyhow::Result<DataRow>>> {
+    let reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(file_path)?;
+    let rows = reader.into_records().enumerate().map(|(i, record)| {
+        let record = record?;
+        let [user, timestamp, transaction_id] = [0, 1, 2].map(|field| record.get(field));
+        let (Some(user), Some(timestamp), Some(transaction_id)) = (user, timestamp, transaction_id)
+        else {
+            anyhow::bail!("invalid data row {i}: {record:?}");
+        };
+        Ok(DataRow {
+            user: user.to_owned(),
+            timestamp: timestamp.to_owned(),
+            transaction_id: transaction_id.to_owned(),
+        })
+    });
+    Ok(rows)
+}
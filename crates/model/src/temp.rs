@@ -32,17 +32,185 @@ impl RespFilesManager {
         tar_file_path: &std::path::Path,
         dst_file_path: &std::path::Path,
     ) -> anyhow::Result<()> {
-        let mut csv_reader = csv::Reader::from_path(csv_file_path)?;
-        let mut tar_file = tar::Builder::new(std::fs::File::create(&tar_file_path)?);
+        let mut tar_file = tar::Builder::new(std::fs::File::create(tar_file_path)?);
         let mut tar_header = tar::Header::new_gnu();
         let dst_file_name = dst_file_path.file_name().unwrap();
         let mut writer = tar_file.append_writer(&mut tar_header, dst_file_name)?;
+        Self::write_commands(csv_file_path, &mut writer)?;
+        Ok(())
+    }
+
+    /// Encodes the data file into RESP commands kept in memory.
+    ///
+    /// Returns the commands together with their count.
+    pub fn encode_data_file(csv_file_path: &std::path::Path) -> anyhow::Result<(Vec<u8>, u64)> {
+        let mut commands = vec![];
+        let commands_count = Self::write_commands(csv_file_path, &mut commands)?;
+        Ok((commands, commands_count))
+    }
+
+    /// Encodes arbitrary commands, e.g. the queries of a workload.
+    pub fn encode_commands<S: AsRef<str>>(commands: &[Vec<S>]) -> Vec<u8> {
+        let mut encoded = vec![];
+        for command in commands {
+            let command = command.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+            encoded.extend_from_slice(&resp::encode_slice(&command));
+        }
+        encoded
+    }
+
+    /// Archives encoded commands as `dst_file_path` next to the cached data file
+    /// archive, under `{name}` instead of the data file stem.
+    pub fn tar_commands(
+        csv_file_path: &std::path::Path,
+        name: &str,
+        commands: &[u8],
+        dst_file_path: &std::path::Path,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let tar_file_path = csv_file_path
+            .with_file_name("resp")
+            .join(name)
+            .with_extension("tar");
+        std::fs::create_dir_all(tar_file_path.parent().unwrap())?;
+        let mut tar_file = tar::Builder::new(std::fs::File::create(&tar_file_path)?);
+        let mut tar_header = tar::Header::new_gnu();
+        tar_header.set_size(commands.len() as u64);
+        tar_header.set_mode(0o644);
+        let dst_file_name = dst_file_path.file_name().ok_or(anyhow::anyhow!(
+            "invalid file path: {}",
+            dst_file_path.display()
+        ))?;
+        tar_file.append_data(&mut tar_header, dst_file_name, commands)?;
+        tar_file.finish()?;
+        Ok(tar_file_path)
+    }
+
+    fn write_commands(
+        csv_file_path: &std::path::Path,
+        writer: &mut impl Write,
+    ) -> anyhow::Result<u64> {
+        // Data files are written without a header row
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(csv_file_path)?;
+        let mut commands_count = 0;
         for result in csv_reader.records() {
             let record = result?;
             let command = Self::redis_insert_command(&record);
             let resp_item = resp::encode_slice(&command);
             writer.write_all(&resp_item)?;
+            commands_count += 1;
         }
-        Ok(())
+        Ok(commands_count)
+    }
+}
+
+pub struct CsvFilesManager;
+
+impl CsvFilesManager {
+    /// Archives the data file as is, for loaders reading CSV themselves.
+    pub fn tar_data_file(
+        csv_file_path: &std::path::Path,
+        dst_file_path: &std::path::Path,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let tar_file_stem = csv_file_path.file_stem().ok_or(anyhow::anyhow!(
+            "invalid file path: {}",
+            csv_file_path.display()
+        ))?;
+        // `..some path/csv/_csv_file_stem_.tar`
+        let tar_file_path = csv_file_path
+            .with_file_name("csv")
+            .join(tar_file_stem)
+            .with_extension("tar");
+        if !tar_file_path.exists() {
+            std::fs::create_dir_all(tar_file_path.parent().unwrap())?;
+            let mut tar_file = tar::Builder::new(std::fs::File::create(&tar_file_path)?);
+            let dst_file_name = dst_file_path.file_name().ok_or(anyhow::anyhow!(
+                "invalid file path: {}",
+                dst_file_path.display()
+            ))?;
+            tar_file.append_path_with_name(csv_file_path, dst_file_name)?;
+            tar_file.finish()?;
+        }
+        Ok(tar_file_path)
+    }
+
+    /// Archives data encoded from the data file as `dst_file_path`, next to
+    /// the cached data file archive under `{name}` instead of its stem.
+    pub fn tar_encoded(
+        csv_file_path: &std::path::Path,
+        name: &str,
+        encoded: &[u8],
+        dst_file_path: &std::path::Path,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let tar_file_path = csv_file_path
+            .with_file_name("csv")
+            .join(name)
+            .with_extension("tar");
+        std::fs::create_dir_all(tar_file_path.parent().unwrap())?;
+        let mut tar_file = tar::Builder::new(std::fs::File::create(&tar_file_path)?);
+        let mut tar_header = tar::Header::new_gnu();
+        tar_header.set_size(encoded.len() as u64);
+        tar_header.set_mode(0o644);
+        let dst_file_name = dst_file_path.file_name().ok_or(anyhow::anyhow!(
+            "invalid file path: {}",
+            dst_file_path.display()
+        ))?;
+        tar_file.append_data(&mut tar_header, dst_file_name, encoded)?;
+        tar_file.finish()?;
+        Ok(tar_file_path)
+    }
+}
+
+pub struct JsonLinesFilesManager;
+
+impl JsonLinesFilesManager {
+    /// Archives the data file encoded into JSON Lines documents, for
+    /// document stores importing them as is.
+    pub fn tar_data_file(
+        csv_file_path: &std::path::Path,
+        dst_file_path: &std::path::Path,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let tar_file_stem = csv_file_path.file_stem().ok_or(anyhow::anyhow!(
+            "invalid file path: {}",
+            csv_file_path.display()
+        ))?;
+        // `..some path/ejson/_csv_file_stem_.tar`
+        let tar_file_path = csv_file_path
+            .with_file_name("ejson")
+            .join(tar_file_stem)
+            .with_extension("tar");
+        if !tar_file_path.exists() {
+            std::fs::create_dir_all(tar_file_path.parent().unwrap())?;
+            let (documents, _) = Self::encode_data_file(csv_file_path)?;
+            let mut tar_file = tar::Builder::new(std::fs::File::create(&tar_file_path)?);
+            let mut tar_header = tar::Header::new_gnu();
+            tar_header.set_size(documents.len() as u64);
+            tar_header.set_mode(0o644);
+            let dst_file_name = dst_file_path.file_name().ok_or(anyhow::anyhow!(
+                "invalid file path: {}",
+                dst_file_path.display()
+            ))?;
+            tar_file.append_data(&mut tar_header, dst_file_name, documents.as_slice())?;
+            tar_file.finish()?;
+        }
+        Ok(tar_file_path)
+    }
+
+    /// Encodes the data file into JSON Lines documents kept in memory.
+    ///
+    /// Returns the documents together with their count.
+    pub fn encode_data_file(csv_file_path: &std::path::Path) -> anyhow::Result<(Vec<u8>, u64)> {
+        let mut documents = vec![];
+        let mut documents_count = 0;
+        for row in crate::read_data_file(csv_file_path)? {
+            let timestamp = row
+                .timestamp
+                .parse()
+                .map_err(|err| anyhow::anyhow!("invalid timestamp of {row:?}: {err}"))?;
+            crate::write_json_document(&mut documents, &row.user, timestamp, &row.transaction_id)?;
+            documents_count += 1;
+        }
+        Ok((documents, documents_count))
     }
 }
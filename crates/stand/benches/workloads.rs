@@ -0,0 +1,172 @@
+use db_test_compare::workload::{Workload, WorkloadInput};
+use db_test_compare::*;
+use db_test_model::list_data_files;
+
+use std::time::{Duration, Instant};
+
+fn workload_bencher(b: &mut criterion::Bencher, context: &Context, bench_input: &WorkloadInput) {
+    // Only the measure phase is timed, the phases around it are not
+    b.to_async(&context.runtime).iter_custom(async |iters| {
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..iters {
+            let prepared = context.backend.prepare(bench_input).await.unwrap();
+            let started = Instant::now();
+            // We hold the running container
+            let outcome = prepared.bench.await.unwrap();
+            elapsed += started.elapsed();
+            context.verify(bench_input, &outcome).await.unwrap();
+            context.teardown(outcome).await.unwrap();
+        }
+        elapsed
+    });
+}
+
+/// Loads `bench_input` once outside of the timings and publishes what it left behind.
+///
+/// The footprint is reported per row stored, whatever the number of operations.
+fn probe_and_report(context: &Context, benchmark: String, bench_input: &WorkloadInput) -> Probe {
+    // Footprint is measured on a load of its own, outside of the timings
+    let probe = context.probe(bench_input).unwrap();
+    let report = report::LoadReport {
+        benchmark,
+        dataset: bench_input
+            .file_path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned(),
+        rows: bench_input.expected_rows().unwrap(),
+        elapsed: probe.elapsed,
+        loader: probe.loader,
+        footprint: probe.footprint,
+        phases: probe.phases.clone(),
+    };
+    report.publish().unwrap();
+    probe
+}
+
+/// Keeps backends, storages and load modes apart, so they can be compared.
+fn function_name(context: &Context, workload: Workload) -> String {
+    format!(
+        "{workload}/{}/{}/{}",
+        context.backend.name(),
+        context.config.storage.name(),
+        context.config.load_mode.name()
+    )
+}
+
+fn workload_bench_group(c: &mut criterion::Criterion, context: &Context, workload: Workload) {
+    let mut group = c.benchmark_group(format!("bench.{workload}"));
+    let function_name = function_name(context, workload);
+    for (i, file_path) in list_data_files().unwrap().enumerate() {
+        let bench_input = WorkloadInput::new(workload, &file_path);
+        let operations = bench_input.operations_count().unwrap();
+        let benchmark = format!("{function_name}/{i}");
+        probe_and_report(context, benchmark, &bench_input);
+        group.throughput(criterion::Throughput::Elements(operations));
+        group.bench_function(criterion::BenchmarkId::new(&function_name, i), |b| {
+            workload_bencher(b, context, &bench_input);
+        });
+    }
+    group.finish();
+}
+
+/// Splits every dataset across `1, 2, 4, … max_clients` concurrent clients
+/// and prints the throughput and latency at each level.
+fn scaling_bench_group(
+    c: &mut criterion::Criterion,
+    context: &Context,
+    workload: Workload,
+    max_clients: usize,
+) {
+    let mut group = c.benchmark_group(format!("scaling.{workload}"));
+    let function_name = function_name(context, workload);
+    for (i, file_path) in list_data_files().unwrap().enumerate() {
+        let mut curve = scaling::ScalingCurve::new(format!("{function_name}/{i}"));
+        for clients in scaling::levels(max_clients) {
+            let bench_input = WorkloadInput::new(workload, &file_path).with_clients(clients);
+            let operations = bench_input.operations_count().unwrap();
+            let benchmark = format!("{function_name}/{i}/{clients}");
+            let probe = probe_and_report(context, benchmark, &bench_input);
+            curve.push(scaling::ScalingPoint {
+                clients,
+                operations,
+                elapsed: probe.elapsed,
+                latency: probe.loader.latency,
+            });
+            group.throughput(criterion::Throughput::Elements(operations));
+            let id = criterion::BenchmarkId::new(format!("{function_name}/{i}"), clients);
+            group.bench_function(id, |b| {
+                workload_bencher(b, context, &bench_input);
+            });
+        }
+        report::publish_curve(&curve);
+    }
+    group.finish();
+}
+
+/// Runs the workloads selected by `DB_TEST_WORKLOADS` on every backend
+/// selected by `DB_TEST_BACKENDS`, skipping the ones a backend does not support
+/// and the backends without the configured load mode.
+///
+/// Scaling benchmarks run in the driver load mode when `DB_TEST_MAX_CLIENTS` is set.
+fn workloads_benchmark(c: &mut criterion::Criterion) {
+    let workloads = Workload::from_env().unwrap();
+    let max_clients = scaling::max_clients_from_env().unwrap();
+    for factory in registry::from_env().unwrap() {
+        let context = match Context::new(factory) {
+            Ok(context) => context,
+            Err(err) if err.is::<runtime::Unreachable>() => {
+                eprintln!("skipping {} benchmarks: {err}", factory.name);
+                continue;
+            }
+            Err(err) => panic!("cannot set up {}: {err:#}", factory.name),
+        };
+        if !context
+            .backend
+            .load_modes()
+            .contains(&context.config.load_mode)
+        {
+            eprintln!(
+                "skipping {} benchmarks: no {} load mode",
+                factory.name,
+                context.config.load_mode.name()
+            );
+            continue;
+        }
+        let max_clients = match max_clients {
+            Some(_) if context.config.load_mode != LoadMode::Driver => {
+                eprintln!(
+                    "skipping {} scaling benchmarks: {} needs the driver load mode, not {}",
+                    factory.name,
+                    scaling::MAX_CLIENTS_ENV,
+                    context.config.load_mode.name()
+                );
+                None
+            }
+            max_clients => max_clients,
+        };
+        let _enter = context.runtime.enter();
+        for workload in &workloads {
+            if !context.backend.workloads().contains(workload) {
+                continue;
+            }
+            workload_bench_group(c, &context, *workload);
+            if let Some(max_clients) = max_clients {
+                scaling_bench_group(c, &context, *workload, max_clients);
+            }
+        }
+    }
+}
+
+criterion::criterion_group! {
+    name = workloads;
+    config = criterion::Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(10))
+        .measurement_time(Duration::from_secs(60))
+        .noise_threshold(0.05);
+    targets = workloads_benchmark
+}
+
+criterion::criterion_main!(workloads);